@@ -3,7 +3,9 @@
 ## Why no changes in a while?
 
 This library is a thoroughly-tested boilerplate generator, is code complete, has
-no unsafe, and is vanishingly unlikely to have any security issues to patch.
+no unsafe outside of a checked pointer cast and the opt-in `unchecked` methods
+(all of which are tested under Miri), and is vanishingly unlikely to have any
+security issues to patch.
 
 ## License
 
@@ -0,0 +1,58 @@
+//! Compares the generated `downcast`, which compares `TypeId`s once, against checking `is` before
+//! `Any::downcast`, which compares them twice:
+//!
+//! ```sh
+//! cargo bench --bench downcast
+//! ```
+// Benchmarks need `black_box` (Rust 1.66) rather than the minimum supported version.
+#![allow(unknown_lints, clippy::incompatible_msrv)]
+
+#[macro_use]
+extern crate downcast_rs;
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+use downcast_rs::Downcast;
+
+trait Base: Downcast {}
+impl_downcast!(Base);
+
+struct Foo(u64);
+impl Base for Foo {}
+struct Bar;
+impl Base for Bar {}
+
+const ITERS: usize = 10_000_000;
+const BATCH: usize = 10_000;
+
+// The body `downcast` used to have.
+fn downcast_twice<T: Base>(object: Box<dyn Base>) -> Result<Box<T>, Box<dyn Base>> {
+    if object.is::<T>() {
+        Ok(Downcast::into_any(object).downcast::<T>().unwrap())
+    } else {
+        Err(object)
+    }
+}
+
+// Times `f` on a batch of boxed objects alternating between the types, excluding their creation.
+fn bench<F: FnMut(Box<dyn Base>) -> u64>(name: &str, mut f: F) {
+    let mut elapsed = Duration::new(0, 0);
+    let mut sum = 0;
+    for _ in 0..ITERS / BATCH {
+        let objects: Vec<Box<dyn Base>> = (0..BATCH)
+            .map(|i| -> Box<dyn Base> { if i % 2 == 0 { Box::new(Foo(1)) } else { Box::new(Bar) } })
+            .collect();
+        let start = Instant::now();
+        for object in objects {
+            sum += f(black_box(object));
+        }
+        elapsed += start.elapsed();
+    }
+    assert_eq!(sum, (ITERS / 2) as u64);
+    println!("{:<16} {:>8.3} ns/iter", name, elapsed.as_secs_f64() * 1e9 / ITERS as f64);
+}
+
+fn main() {
+    bench("downcast", |object| object.downcast::<Foo>().map_or(0, |object| object.0));
+    bench("is + downcast", |object| downcast_twice::<Foo>(object).map_or(0, |object| object.0));
+}
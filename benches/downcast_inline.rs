@@ -0,0 +1,55 @@
+//! Compares `downcast`, which keeps the object in its box, against `downcast_inline`, which moves
+//! it out onto the stack, for a small and a large concrete type:
+//!
+//! ```sh
+//! cargo bench --bench downcast_inline
+//! ```
+// Benchmarks need `black_box` (Rust 1.66) rather than the minimum supported version.
+#![allow(unknown_lints, clippy::incompatible_msrv)]
+
+#[macro_use]
+extern crate downcast_rs;
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+use downcast_rs::Downcast;
+
+trait Base: Downcast {}
+impl_downcast!(Base extras => [downcast_inline]);
+
+struct Small(u64);
+impl Base for Small {}
+#[allow(dead_code)] // Only ever moved around.
+struct Large([u64; 4096]);
+impl Base for Large {}
+
+const ITERS: usize = 20_000;
+const BATCH: usize = 1_000;
+
+// Times `f` on freshly boxed objects, excluding their creation.
+fn bench<T, F, G>(name: &str, make: F, mut f: G)
+where
+    F: Fn() -> Box<dyn Base>,
+    G: FnMut(Box<dyn Base>) -> T,
+{
+    let mut elapsed = Duration::new(0, 0);
+    for _ in 0..ITERS / BATCH {
+        let objects: Vec<Box<dyn Base>> = (0..BATCH).map(|_| make()).collect();
+        let start = Instant::now();
+        for object in objects {
+            black_box(f(black_box(object)));
+        }
+        elapsed += start.elapsed();
+    }
+    println!("{:<22} {:>10.3} ns/iter", name, elapsed.as_secs_f64() * 1e9 / ITERS as f64);
+}
+
+fn main() {
+    let small = || -> Box<dyn Base> { Box::new(Small(1)) };
+    bench("small downcast", small, |object| object.downcast::<Small>().ok().map(|small| small.0));
+    bench("small downcast_inline", small, |object| object.downcast_inline::<Small>().ok().map(|small| small.0));
+
+    let large = || -> Box<dyn Base> { Box::new(Large([1; 4096])) };
+    bench("large downcast", large, |object| object.downcast::<Large>().ok());
+    bench("large downcast_inline", large, |object| object.downcast_inline::<Large>().ok());
+}
@@ -0,0 +1,44 @@
+//! Compares the generated `is` against calling `Any::is` directly. Run with and without the
+//! `const-type-id` feature to compare both ways of obtaining the requested `TypeId`:
+//!
+//! ```sh
+//! cargo bench --bench is
+//! cargo bench --bench is --features const-type-id
+//! ```
+// Benchmarks need `black_box` (Rust 1.66) rather than the minimum supported version.
+#![allow(unknown_lints, clippy::incompatible_msrv)]
+
+#[macro_use]
+extern crate downcast_rs;
+
+use std::hint::black_box;
+use std::time::Instant;
+use downcast_rs::Downcast;
+
+trait Base: Downcast {}
+impl_downcast!(Base);
+
+struct Foo;
+impl Base for Foo {}
+struct Bar;
+impl Base for Bar {}
+
+const ITERS: u32 = 10_000_000;
+
+fn bench<F: FnMut(&dyn Base) -> bool>(name: &str, objects: &[Box<dyn Base>], mut f: F) {
+    let start = Instant::now();
+    let mut matches = 0u32;
+    for i in 0..ITERS {
+        let object = black_box(&*objects[i as usize % objects.len()]);
+        matches += f(object) as u32;
+    }
+    let elapsed = start.elapsed();
+    black_box(matches);
+    println!("{:<10} {:>8.3} ns/iter", name, elapsed.as_secs_f64() * 1e9 / f64::from(ITERS));
+}
+
+fn main() {
+    let objects: Vec<Box<dyn Base>> = vec![Box::new(Foo), Box::new(Bar), Box::new(Foo)];
+    bench("Any::is", &objects, |object| Downcast::as_any(object).is::<Foo>());
+    bench("is", &objects, |object| object.is::<Foo>());
+}
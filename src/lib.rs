@@ -1,4 +1,6 @@
-#![deny(unsafe_code,rustdoc::bare_urls)]
+#![deny(unsafe_code)]
+// `rustdoc::bare_urls` is denied in CI through `RUSTDOCFLAGS` instead, since the `rustdoc` lint
+// tool is unknown to (and so rejected by) compilers before 1.52.
 #![cfg_attr(not(feature = "std"), no_std)]
 //! [![Build status](https://img.shields.io/github/actions/workflow/status/marcianx/downcast-rs/main.yml?branch=master)](https://github.com/marcianx/downcast-rs/actions)
 //! [![Latest version](https://img.shields.io/crates/v/downcast-rs.svg)](https://crates.io/crates/downcast-rs)
@@ -11,7 +13,9 @@
 //! inlined implementations.
 //!
 //! `downcast-rs` adds this downcasting support to trait objects using only safe
-//! Rust. It supports **type parameters**, **associated types**, and **constraints**.
+//! Rust, but for a pointer cast after a `TypeId` check that spares checking it twice
+//! and the methods of the opt-in `unchecked` option. It supports **type
+//! parameters**, **associated types**, and **constraints**.
 //!
 //! # Usage
 //!
@@ -34,12 +38,26 @@
 //! below.
 //!
 //! Since 1.2.0, the minimum supported Rust version is 1.36 due to needing stable access to alloc.
+//! The next release raises it to 1.38 due to needing `core::any::type_name`.
+//!
+//! With the `const-type-id` feature (requiring Rust 1.91), the `TypeId` that `is` compares
+//! against is computed at compile time. The object's own `TypeId` is still looked up at runtime
+//! through the trait object's vtable, which `const fn`s cannot call, so `is` and the downcasting
+//! methods cannot be used in const contexts. Trait objects created in a `const` or `static`, e.g.
+//! `const OBJ: &dyn Trait = &Foo;`, are downcast at runtime like any other.
+//! The `once-lock` feature (requiring Rust 1.70) adds `downcast_static` for trait objects in a
+//! `static` `OnceLock`, `register_capability` for `has_capability` to query, and
+//! `register_type_code` assigning the codes returned by `concrete_type_code`.
+//! The `const-generics` feature (requiring Rust 1.63) adds `downcast_array_ref` for arrays of
+//! boxed trait objects.
+//! The `inventory` feature (requiring Rust 1.62) adds `register_downcast!`, which registers
+//! concrete types of a trait at link time for lookup via `registered_types`.
 //!
 #![cfg_attr(feature = "sync", doc = "```")]
 #![cfg_attr(not(feature = "sync"), doc = "```ignore")]
 //! # #[macro_use]
 //! # extern crate downcast_rs;
-//! # use downcast_rs::{Downcast, DowncastSync};
+//! # use downcast_rs::{Downcast, DowncastSend, DowncastSync};
 //! trait Trait: Downcast {}
 //! impl_downcast!(Trait);
 //!
@@ -47,6 +65,12 @@
 //! // and starting `impl_downcast!` with `sync`.
 //! trait TraitSync: DowncastSync {}
 //! impl_downcast!(sync TraitSync);
+//! // `sync` composes with the other forms, e.g. `sync concrete` or `concrete sync`.
+//!
+//! // Also supports converting boxed trait objects into `Box<dyn Any + Send>` without
+//! // requiring `Sync` by extending `DowncastSend` and starting `impl_downcast!` with `send`.
+//! trait TraitSend: DowncastSend {}
+//! impl_downcast!(send TraitSend);
 //!
 //! // With type parameters.
 //! trait TraitGeneric1<T>: Downcast {}
@@ -62,12 +86,33 @@
 //! }
 //! impl_downcast!(TraitGeneric3<T> assoc H where T: Copy, H: Clone);
 //!
+//! // With constraints given inline on type parameters, merged with the `where` clause.
+//! trait TraitGeneric5<T: Copy, U: Into<Vec<u8>>>: Downcast { type H: Clone; }
+//! impl_downcast!(TraitGeneric5<T: Copy, U: Into<Vec<u8>>> assoc H where H: Clone);
+//!
+//! // With lifetime parameters, which downcasting requires to be `'static`.
+//! trait TraitGeneric4<'a, T: Clone>: Downcast { type H: Copy; }
+//! impl_downcast!(TraitGeneric4<'a, T> assoc H where T: Clone, H: Copy);
+//!
 //! // With concrete types.
 //! trait TraitConcrete1<T: Copy>: Downcast {}
 //! impl_downcast!(concrete TraitConcrete1<u32>);
 //!
 //! trait TraitConcrete2<T: Copy>: Downcast { type H; }
 //! impl_downcast!(concrete TraitConcrete2<u32> assoc H=f64);
+//!
+//! // With const parameters (requires Rust 1.51), generic or concrete.
+//! trait TraitConst1<const N: usize>: Downcast {}
+//! impl_downcast!(TraitConst1<const N: usize>);
+//!
+//! trait TraitConst2<T, const N: usize>: Downcast {}
+//! impl_downcast!(concrete TraitConst2<u32, 4>);
+//!
+//! // With a `clone_box` method cloning any of the listed concrete types.
+//! trait TraitClone: Downcast {}
+//! # #[derive(Clone)] struct Foo;
+//! # impl TraitClone for Foo {}
+//! impl_downcast!(TraitClone clone => [Foo]);
 //! # fn main() {}
 //! ```
 //!
@@ -97,7 +142,7 @@
 //!
 //! fn main() {
 //!     // Create a trait object.
-//!     let mut base: Box<Base> = Box::new(Foo(42));
+//!     let mut base: Box<dyn Base> = Box::new(Foo(42));
 //!
 //!     // Try sequential downcasts.
 //!     if let Some(foo) = base.downcast_ref::<Foo>() {
@@ -108,19 +153,19 @@
 //!
 //!     assert!(base.is::<Foo>());
 //!
-//!     // Fail to convert `Box<Base>` into `Box<Bar>`.
+//!     // Fail to convert `Box<dyn Base>` into `Box<Bar>`.
 //!     let res = base.downcast::<Bar>();
 //!     assert!(res.is_err());
 //!     let base = res.unwrap_err();
-//!     // Convert `Box<Base>` into `Box<Foo>`.
+//!     // Convert `Box<dyn Base>` into `Box<Foo>`.
 //!     assert_eq!(42, base.downcast::<Foo>().map_err(|_| "Shouldn't happen.").unwrap().0);
 //!
 //!     // Also works with `Rc`.
-//!     let mut rc: Rc<Base> = Rc::new(Foo(42));
+//!     let mut rc: Rc<dyn Base> = Rc::new(Foo(42));
 //!     assert_eq!(42, rc.downcast_rc::<Foo>().map_err(|_| "Shouldn't happen.").unwrap().0);
 //!
 //!     // Since this trait is `Sync`, it also supports `Arc` downcasts.
-//!     let mut arc: Arc<Base> = Arc::new(Foo(42));
+//!     let mut arc: Arc<dyn Base> = Arc::new(Foo(42));
 //!     assert_eq!(42, arc.downcast_arc::<Foo>().map_err(|_| "Shouldn't happen.").unwrap().0);
 //! }
 //! ```
@@ -146,7 +191,7 @@
 //!
 //! fn main() {
 //!     // Create a trait object.
-//!     let mut base: Box<Base<u32, H=f32>> = Box::new(Bar(42.0));
+//!     let mut base: Box<dyn Base<u32, H=f32>> = Box::new(Bar(42.0));
 //!
 //!     // Try sequential downcasts.
 //!     if let Some(foo) = base.downcast_ref::<Foo>() {
@@ -168,27 +213,51 @@ pub extern crate core as __std;
 pub extern crate std as __std;
 #[doc(hidden)]
 pub extern crate alloc as __alloc;
+#[cfg(feature = "inventory")]
+#[doc(hidden)]
+pub extern crate inventory as __inventory;
 
-use __std::any::Any;
-use __alloc::{boxed::Box, rc::Rc};
+use __std::alloc::Layout;
+use __std::any::{Any, TypeId};
+use __std::fmt;
+use __std::marker::PhantomData;
+use __std::pin::Pin;
+use __alloc::{boxed::Box, rc::{Rc, Weak}, vec::Vec};
+use __alloc::collections::{btree_map, BTreeMap};
 
 #[cfg(feature = "sync")]
 use __alloc::sync::Arc;
 
 /// Supports conversion to `Any`. Traits to be extended by `impl_downcast!` must extend `Downcast`.
+///
+/// The conversions may also be called directly to build other downcasting utilities, e.g.
+/// `Downcast::into_any_rc(rc).downcast::<ConcreteType>()`. The `Arc` counterpart,
+/// `into_any_arc`, belongs to `DowncastSync` since `Arc<dyn Any + Send + Sync>` requires the
+/// object to be `Send + Sync`; a second `into_any_arc` here would make calls to it ambiguous.
 pub trait Downcast: Any {
     /// Convert `Box<dyn Trait>` (where `Trait: Downcast`) to `Box<dyn Any>`. `Box<dyn Any>` can
     /// then be further `downcast` into `Box<ConcreteType>` where `ConcreteType` implements `Trait`.
     fn into_any(self: Box<Self>) -> Box<dyn Any>;
-    /// Convert `Rc<Trait>` (where `Trait: Downcast`) to `Rc<Any>`. `Rc<Any>` can then be
-    /// further `downcast` into `Rc<ConcreteType>` where `ConcreteType` implements `Trait`.
+    /// Convert `Rc<dyn Trait>` (where `Trait: Downcast`) to `Rc<dyn Any>`. `Rc<dyn Any>` can
+    /// then be further `downcast` into `Rc<ConcreteType>` where `ConcreteType` implements `Trait`.
     fn into_any_rc(self: Rc<Self>) -> Rc<dyn Any>;
-    /// Convert `&Trait` (where `Trait: Downcast`) to `&Any`. This is needed since Rust cannot
-    /// generate `&Any`'s vtable from `&Trait`'s.
+    /// Convert `&dyn Trait` (where `Trait: Downcast`) to `&dyn Any`. This is needed since Rust
+    /// cannot generate `&dyn Any`'s vtable from `&dyn Trait`'s.
     fn as_any(&self) -> &dyn Any;
-    /// Convert `&mut Trait` (where `Trait: Downcast`) to `&Any`. This is needed since Rust cannot
-    /// generate `&mut Any`'s vtable from `&mut Trait`'s.
+    /// Convert `&mut dyn Trait` (where `Trait: Downcast`) to `&mut dyn Any`. This is needed since
+    /// Rust cannot generate `&mut dyn Any`'s vtable from `&mut dyn Trait`'s.
     fn as_any_mut(&mut self) -> &mut dyn Any;
+    /// Returns the name of the concrete type behind `&dyn Trait` (where `Trait: Downcast`). Used by
+    /// the `type_name` method generated by `impl_downcast!` with `extras => [type_name]`: as a
+    /// method of `Downcast`, it would also be callable on `Box<dyn Trait>` and name the `Box`
+    /// instead.
+    #[doc(hidden)]
+    fn __type_name(&self) -> &'static str;
+    /// Returns the layout of the concrete type behind `&dyn Trait` (where `Trait: Downcast`). Used
+    /// by the `concrete_layout` method generated by `impl_downcast!`, for the same reason as
+    /// `__type_name`.
+    #[doc(hidden)]
+    fn __concrete_layout(&self) -> Layout;
 }
 
 impl<T: Any> Downcast for T {
@@ -196,391 +265,4626 @@ impl<T: Any> Downcast for T {
     fn into_any_rc(self: Rc<Self>) -> Rc<dyn Any> { self }
     fn as_any(&self) -> &dyn Any { self }
     fn as_any_mut(&mut self) -> &mut dyn Any { self }
+    fn __type_name(&self) -> &'static str { __std::any::type_name::<Self>() }
+    fn __concrete_layout(&self) -> Layout { Layout::new::<Self>() }
 }
 
 #[cfg(feature = "sync")]
 /// Extends `Downcast` to support `Sync` traits that thus support `Arc` downcasting as well.
 pub trait DowncastSync: Downcast + Send + Sync {
-    /// Convert `Arc<Trait>` (where `Trait: Downcast`) to `Arc<Any>`. `Arc<Any>` can then be
-    /// further `downcast` into `Arc<ConcreteType>` where `ConcreteType` implements `Trait`.
+    /// Convert `Arc<dyn Trait>` (where `Trait: Downcast`) to `Arc<dyn Any>`. `Arc<dyn Any>` can
+    /// then be further `downcast` into `Arc<ConcreteType>` where `ConcreteType` implements
+    /// `Trait`.
     fn into_any_arc(self: Arc<Self>) -> Arc<dyn Any + Send + Sync>;
+    /// Convert `&dyn Trait` (where `Trait: DowncastSync`) to `&(dyn Any + Send + Sync)`, which,
+    /// unlike the `&dyn Any` of `as_any`, can still be shared with other threads.
+    fn as_any_sync(&self) -> &(dyn Any + Send + Sync);
+    /// Convert `&mut dyn Trait` (where `Trait: DowncastSync`) to `&mut (dyn Any + Send + Sync)`.
+    fn as_any_sync_mut(&mut self) -> &mut (dyn Any + Send + Sync);
 }
 
 #[cfg(feature = "sync")]
 impl<T: Any + Send + Sync> DowncastSync for T {
     fn into_any_arc(self: Arc<Self>) -> Arc<dyn Any + Send + Sync> { self }
+    fn as_any_sync(&self) -> &(dyn Any + Send + Sync) { self }
+    fn as_any_sync_mut(&mut self) -> &mut (dyn Any + Send + Sync) { self }
 }
 
-/// Adds downcasting support to traits that extend `downcast::Downcast` by defining forwarding
-/// methods to the corresponding implementations on `std::any::Any` in the standard library.
-///
-/// See <https://users.rust-lang.org/t/how-to-create-a-macro-to-impl-a-provided-type-parametrized-trait/5289>
-/// for why this is implemented this way to support templatized traits.
-#[macro_export(local_inner_macros)]
-macro_rules! impl_downcast {
-    (@impl_full
-        $trait_:ident [$($param_types:tt)*]
-        for [$($forall_types:ident),*]
-        where [$($preds:tt)*]
-    ) => {
-        impl_downcast! {
-            @inject_where
-                [impl<$($forall_types),*> dyn $trait_<$($param_types)*>]
-                types [$($forall_types),*]
-                where [$($preds)*]
-                [{
-                    impl_downcast! { @impl_body $trait_ [$($param_types)*] }
-                }]
-        }
-    };
+/// Extends `Downcast` to support `Send` traits whose boxed objects can be converted into
+/// `Box<dyn Any + Send>`, e.g. to hand them to an API taking those, without requiring `Sync`.
+pub trait DowncastSend: Downcast + Send {
+    /// Convert `Box<dyn Trait>` (where `Trait: DowncastSend`) to `Box<dyn Any + Send>`, which can
+    /// still be sent to another thread and then `downcast` into `Box<ConcreteType>`.
+    fn into_any_send(self: Box<Self>) -> Box<dyn Any + Send>;
+}
 
-    (@impl_full_sync
-        $trait_:ident [$($param_types:tt)*]
-        for [$($forall_types:ident),*]
-        where [$($preds:tt)*]
-    ) => {
-        impl_downcast! {
-            @inject_where
-                [impl<$($forall_types),*> dyn $trait_<$($param_types)*>]
-                types [$($forall_types),*]
-                where [$($preds)*]
-                [{
-                    impl_downcast! { @impl_body $trait_ [$($param_types)*] }
-                    impl_downcast! { @impl_body_sync $trait_ [$($param_types)*] }
-                }]
-        }
-    };
+impl<T: Any + Send> DowncastSend for T {
+    fn into_any_send(self: Box<Self>) -> Box<dyn Any + Send> { self }
+}
 
-    (@impl_body $trait_:ident [$($types:tt)*]) => {
-        /// Returns true if the trait object wraps an object of type `__T`.
-        #[inline]
-        pub fn is<__T: $trait_<$($types)*>>(&self) -> bool {
-            $crate::Downcast::as_any(self).is::<__T>()
-        }
-        /// Returns a boxed object from a boxed trait object if the underlying object is of type
-        /// `__T`. Returns the original boxed trait if it isn't.
-        #[inline]
-        pub fn downcast<__T: $trait_<$($types)*>>(
-            self: $crate::__alloc::boxed::Box<Self>
-        ) -> $crate::__std::result::Result<$crate::__alloc::boxed::Box<__T>, $crate::__alloc::boxed::Box<Self>> {
-            if self.is::<__T>() {
-                Ok($crate::Downcast::into_any(self).downcast::<__T>().unwrap())
-            } else {
-                Err(self)
-            }
-        }
-        /// Returns an `Rc`-ed object from an `Rc`-ed trait object if the underlying object is of
-        /// type `__T`. Returns the original `Rc`-ed trait if it isn't.
-        #[inline]
-        pub fn downcast_rc<__T: $trait_<$($types)*>>(
-            self: $crate::__alloc::rc::Rc<Self>
-        ) -> $crate::__std::result::Result<$crate::__alloc::rc::Rc<__T>, $crate::__alloc::rc::Rc<Self>> {
-            if self.is::<__T>() {
-                Ok($crate::Downcast::into_any_rc(self).downcast::<__T>().unwrap())
-            } else {
-                Err(self)
-            }
-        }
-        /// Returns a reference to the object within the trait object if it is of type `__T`, or
-        /// `None` if it isn't.
-        #[inline]
-        pub fn downcast_ref<__T: $trait_<$($types)*>>(&self) -> $crate::__std::option::Option<&__T> {
-            $crate::Downcast::as_any(self).downcast_ref::<__T>()
-        }
-        /// Returns a mutable reference to the object within the trait object if it is of type
-        /// `__T`, or `None` if it isn't.
-        #[inline]
-        pub fn downcast_mut<__T: $trait_<$($types)*>>(&mut self) -> $crate::__std::option::Option<&mut __T> {
-            $crate::Downcast::as_any_mut(self).downcast_mut::<__T>()
-        }
-    };
+/// Seals a trait for `impl_downcast!(single Trait => Type)`: only `Type` implements
+/// `SingleImpl<Type>`, so only `Type` can implement a trait extending it, e.g.
+/// `trait Trait: Downcast + SingleImpl<Type>`.
+pub trait SingleImpl<T: ?Sized>: single_impl::Sealed<T> {}
 
-    (@impl_body_sync $trait_:ident [$($types:tt)*]) => {
-        /// Returns an `Arc`-ed object from an `Arc`-ed trait object if the underlying object is of
-        /// type `__T`. Returns the original `Arc`-ed trait if it isn't.
-        #[inline]
-        pub fn downcast_arc<__T: $trait_<$($types)*> + $crate::__std::any::Any + $crate::__std::marker::Send + $crate::__std::marker::Sync>(
-            self: $crate::__alloc::sync::Arc<Self>,
-        ) -> $crate::__std::result::Result<$crate::__alloc::sync::Arc<__T>, $crate::__alloc::sync::Arc<Self>>
-        {
-            if self.is::<__T>() {
-                Ok($crate::DowncastSync::into_any_arc(self).downcast::<__T>().unwrap())
-            } else {
-                Err(self)
-            }
-        }
-    };
+impl<T: ?Sized> SingleImpl<T> for T {}
 
-    (@inject_where [$($before:tt)*] types [] where [] [$($after:tt)*]) => {
-        impl_downcast! { @as_item $($before)* $($after)* }
-    };
+mod single_impl {
+    /// Keeps other crates from implementing `SingleImpl`.
+    pub trait Sealed<T: ?Sized> {}
 
-    (@inject_where [$($before:tt)*] types [$($types:ident),*] where [] [$($after:tt)*]) => {
-        impl_downcast! {
-            @as_item
-                $($before)*
-                where $( $types: $crate::__std::any::Any + 'static ),*
-                $($after)*
-        }
-    };
-    (@inject_where [$($before:tt)*] types [$($types:ident),*] where [$($preds:tt)+] [$($after:tt)*]) => {
-        impl_downcast! {
-            @as_item
-                $($before)*
-                where
-                    $( $types: $crate::__std::any::Any + 'static, )*
-                    $($preds)*
-                $($after)*
-        }
-    };
+    impl<T: ?Sized> Sealed<T> for T {}
+}
 
-    (@as_item $i:item) => { $i };
+/// Checks that the trait object type `B` extends `SingleImpl<T>`. Used by the `into_inner` method
+/// generated by `impl_downcast!(single Trait => Type)`.
+#[doc(hidden)]
+pub fn __assert_single_impl<T: ?Sized, B: SingleImpl<T> + ?Sized>() {}
 
-    // No type parameters.
-    ($trait_:ident   ) => { impl_downcast! { @impl_full $trait_ [] for [] where [] } };
-    ($trait_:ident <>) => { impl_downcast! { @impl_full $trait_ [] for [] where [] } };
-    (sync $trait_:ident   ) => { impl_downcast! { @impl_full_sync $trait_ [] for [] where [] } };
-    (sync $trait_:ident <>) => { impl_downcast! { @impl_full_sync $trait_ [] for [] where [] } };
-    // Type parameters.
-    ($trait_:ident < $($types:ident),* >) => {
-        impl_downcast! { @impl_full $trait_ [$($types),*] for [$($types),*] where [] }
-    };
-    (sync $trait_:ident < $($types:ident),* >) => {
-        impl_downcast! { @impl_full_sync $trait_ [$($types),*] for [$($types),*] where [] }
-    };
-    // Type parameters and where clauses.
-    ($trait_:ident < $($types:ident),* > where $($preds:tt)+) => {
-        impl_downcast! { @impl_full $trait_ [$($types),*] for [$($types),*] where [$($preds)*] }
-    };
-    (sync $trait_:ident < $($types:ident),* > where $($preds:tt)+) => {
-        impl_downcast! { @impl_full_sync $trait_ [$($types),*] for [$($types),*] where [$($preds)*] }
-    };
-    // Associated types.
-    ($trait_:ident assoc $($atypes:ident),*) => {
-        impl_downcast! { @impl_full $trait_ [$($atypes = $atypes),*] for [$($atypes),*] where [] }
-    };
-    (sync $trait_:ident assoc $($atypes:ident),*) => {
-        impl_downcast! { @impl_full_sync $trait_ [$($atypes = $atypes),*] for [$($atypes),*] where [] }
-    };
-    // Associated types and where clauses.
-    ($trait_:ident assoc $($atypes:ident),* where $($preds:tt)+) => {
-        impl_downcast! { @impl_full $trait_ [$($atypes = $atypes),*] for [$($atypes),*] where [$($preds)*] }
-    };
-    (sync $trait_:ident assoc $($atypes:ident),* where $($preds:tt)+) => {
-        impl_downcast! { @impl_full_sync $trait_ [$($atypes = $atypes),*] for [$($atypes),*] where [$($preds)*] }
-    };
-    // Type parameters and associated types.
-    ($trait_:ident < $($types:ident),* > assoc $($atypes:ident),*) => {
-        impl_downcast! {
-            @impl_full
-                $trait_ [$($types),*, $($atypes = $atypes),*]
-                for [$($types),*, $($atypes),*]
-                where []
-        }
-    };
-    (sync $trait_:ident < $($types:ident),* > assoc $($atypes:ident),*) => {
-        impl_downcast! {
-            @impl_full_sync
-                $trait_ [$($types),*, $($atypes = $atypes),*]
-                for [$($types),*, $($atypes),*]
-                where []
-        }
-    };
-    // Type parameters, associated types, and where clauses.
-    ($trait_:ident < $($types:ident),* > assoc $($atypes:ident),* where $($preds:tt)+) => {
-        impl_downcast! {
-            @impl_full
-                $trait_ [$($types),*, $($atypes = $atypes),*]
-                for [$($types),*, $($atypes),*]
-                where [$($preds)*]
-        }
-    };
-    (sync $trait_:ident < $($types:ident),* > assoc $($atypes:ident),* where $($preds:tt)+) => {
-        impl_downcast! {
-            @impl_full_sync
-                $trait_ [$($types),*, $($atypes = $atypes),*]
-                for [$($types),*, $($atypes),*]
-                where [$($preds)*]
-        }
-    };
-    // Concretely-parametrized types.
-    (concrete $trait_:ident < $($types:ident),* >) => {
-        impl_downcast! { @impl_full $trait_ [$($types),*] for [] where [] }
-    };
-    (sync concrete $trait_:ident < $($types:ident),* >) => {
-        impl_downcast! { @impl_full_sync $trait_ [$($types),*] for [] where [] }
-    };
-    // Concretely-associated types types.
-    (concrete $trait_:ident assoc $($atypes:ident = $aty:ty),*) => {
-        impl_downcast! { @impl_full $trait_ [$($atypes = $aty),*] for [] where [] }
-    };
-    (sync concrete $trait_:ident assoc $($atypes:ident = $aty:ty),*) => {
-        impl_downcast! { @impl_full_sync $trait_ [$($atypes = $aty),*] for [] where [] }
-    };
-    // Concretely-parametrized types with concrete associated types.
-    (concrete $trait_:ident < $($types:ident),* > assoc $($atypes:ident = $aty:ty),*) => {
-        impl_downcast! { @impl_full $trait_ [$($types),*, $($atypes = $aty),*] for [] where [] }
-    };
-    (sync concrete $trait_:ident < $($types:ident),* > assoc $($atypes:ident = $aty:ty),*) => {
-        impl_downcast! { @impl_full_sync $trait_ [$($types),*, $($atypes = $aty),*] for [] where [] }
-    };
+/// Extends `dyn Any` with the introspection `Downcast` offers where possible, for objects that
+/// are only available as `&dyn Any`.
+///
+/// There is no counterpart to the `type_name` method generated by `impl_downcast!`: unlike the
+/// vtables of traits extending `Downcast`, that of `dyn Any` does not record the name of the
+/// concrete type.
+pub trait AnyExt {
+    /// Returns the layout (size and alignment) of the concrete type behind `&dyn Any`.
+    fn concrete_layout(&self) -> Layout;
 }
 
+impl AnyExt for dyn Any {
+    fn concrete_layout(&self) -> Layout { Layout::for_value(self) }
+}
 
-#[cfg(all(test, feature = "sync"))]
-mod test {
-    macro_rules! test_mod {
-        (
-            $test_mod_name:ident,
-            trait $base_trait:path { $($base_impl:tt)* },
-            non_sync: { $($non_sync_def:tt)+ },
-            sync: { $($sync_def:tt)+ }
-        ) => {
-            test_mod! {
-                $test_mod_name,
-                trait $base_trait { $($base_impl:tt)* },
-                type dyn $base_trait,
-                non_sync: { $($non_sync_def)* },
-                sync: { $($sync_def)* }
-            }
-        };
+impl AnyExt for dyn Any + Send {
+    fn concrete_layout(&self) -> Layout { Layout::for_value(self) }
+}
 
-        (
-            $test_mod_name:ident,
-            trait $base_trait:path { $($base_impl:tt)* },
-            type $base_type:ty,
-            non_sync: { $($non_sync_def:tt)+ },
-            sync: { $($sync_def:tt)+ }
-        ) => {
-            mod $test_mod_name {
-                test_mod!(
-                    @test
-                    $test_mod_name,
-                    test_name: test_non_sync,
-                    trait $base_trait { $($base_impl)* },
-                    type $base_type,
-                    { $($non_sync_def)+ },
-                    []);
+impl AnyExt for dyn Any + Send + Sync {
+    fn concrete_layout(&self) -> Layout { Layout::for_value(self) }
+}
 
-                test_mod!(
-                    @test
-                    $test_mod_name,
-                    test_name: test_sync,
-                    trait $base_trait { $($base_impl)* },
-                    type $base_type,
-                    { $($sync_def)+ },
-                    [{
-                        // Fail to convert Arc<Base> into Arc<Bar>.
-                        let arc: $crate::__alloc::sync::Arc<$base_type> = $crate::__alloc::sync::Arc::new(Foo(42));
-                        let res = arc.downcast_arc::<Bar>();
-                        assert!(res.is_err());
-                        let arc = res.unwrap_err();
-                        // Convert Arc<Base> into Arc<Foo>.
-                        assert_eq!(
-                            42, arc.downcast_arc::<Foo>().map_err(|_| "Shouldn't happen.").unwrap().0);
-                    }]);
-            }
-        };
+/// A concrete type registered with `register_downcast!` as implementing a trait, collected at link
+/// time via the `inventory` crate. Requires the `inventory` feature (and Rust 1.62).
+#[cfg(feature = "inventory")]
+pub struct DowncastRegistration {
+    trait_type_id: fn() -> TypeId,
+    type_id: fn() -> TypeId,
+    type_name: fn() -> &'static str,
+}
 
-        (
-            @test
-            $test_mod_name:ident,
-            test_name: $test_name:ident,
-            trait $base_trait:path { $($base_impl:tt)* },
-            type $base_type:ty,
-            { $($def:tt)+ },
-            [ $($more_tests:block)* ]
-        ) => {
-            #[test]
-            fn $test_name() {
-                #[allow(unused_imports)]
-                use super::super::{Downcast, DowncastSync};
+#[cfg(feature = "inventory")]
+impl DowncastRegistration {
+    #[doc(hidden)]
+    pub const fn __new<B: Upcast<T> + ?Sized + Any, T: Any>() -> Self {
+        DowncastRegistration {
+            trait_type_id: TypeId::of::<B>,
+            type_id: TypeId::of::<T>,
+            type_name: __std::any::type_name::<T>,
+        }
+    }
+    /// Returns the `TypeId` of the trait object type, e.g. `dyn Trait`.
+    pub fn trait_type_id(&self) -> TypeId { (self.trait_type_id)() }
+    /// Returns the `TypeId` of the registered concrete type.
+    pub fn type_id(&self) -> TypeId { (self.type_id)() }
+    /// Returns the name of the registered concrete type.
+    pub fn type_name(&self) -> &'static str { (self.type_name)() }
+    /// Returns true if `obj` is of the registered concrete type.
+    pub fn matches<B: Downcast + ?Sized>(&self, obj: &B) -> bool {
+        Downcast::as_any(obj).type_id() == self.type_id()
+    }
+}
 
-                // Should work even if standard objects (especially those in the prelude) are
-                // aliased to something else.
-                #[allow(dead_code)] struct Any;
-                #[allow(dead_code)] struct Arc;
-                #[allow(dead_code)] struct Box;
-                #[allow(dead_code)] struct Option;
-                #[allow(dead_code)] struct Result;
-                #[allow(dead_code)] struct Rc;
-                #[allow(dead_code)] struct Send;
-                #[allow(dead_code)] struct Sync;
+#[cfg(feature = "inventory")]
+__inventory::collect!(DowncastRegistration);
 
-                // A trait that can be downcast.
-                $($def)*
+/// Returns the concrete types registered with `register_downcast!` for the trait object type `B`
+/// (e.g. `dyn Trait`), in no particular order. Requires the `inventory` feature.
+#[cfg(feature = "inventory")]
+pub fn registered_types<B: ?Sized + Any>() -> impl Iterator<Item = &'static DowncastRegistration> {
+    __inventory::iter::<DowncastRegistration>
+        .into_iter()
+        .filter(|registration| registration.trait_type_id() == TypeId::of::<B>())
+}
 
-                // Concrete type implementing Base.
-                #[derive(Debug)]
-                struct Foo(u32);
-                impl $base_trait for Foo { $($base_impl)* }
-                #[derive(Debug)]
-                struct Bar(f64);
-                impl $base_trait for Bar { $($base_impl)* }
+/// Returns the registration of the concrete type of `obj`, if it was registered with
+/// `register_downcast!` for the trait object type `B`. Requires the `inventory` feature.
+#[cfg(feature = "inventory")]
+pub fn registration_of<B: Downcast + ?Sized>(obj: &B) -> Option<&'static DowncastRegistration> {
+    registered_types::<B>().find(|registration| registration.matches(obj))
+}
 
-                // Functions that can work on references to Base trait objects.
-                fn get_val(base: &$crate::__alloc::boxed::Box<$base_type>) -> u32 {
-                    match base.downcast_ref::<Foo>() {
-                        Some(val) => val.0,
-                        None => 0
-                    }
-                }
-                fn set_val(base: &mut $crate::__alloc::boxed::Box<$base_type>, val: u32) {
-                    if let Some(foo) = base.downcast_mut::<Foo>() {
-                        foo.0 = val;
-                    }
-                }
+/// Returns the `TypeId` of `T`. With the `const-type-id` feature, it is computed at compile time.
+#[doc(hidden)]
+#[inline(always)]
+pub fn __type_id<T: Any + ?Sized>() -> TypeId {
+    #[cfg(feature = "const-type-id")]
+    {
+        TypeIdOf::<T>::ID
+    }
+    #[cfg(not(feature = "const-type-id"))]
+    {
+        TypeId::of::<T>()
+    }
+}
 
-                let mut base: $crate::__alloc::boxed::Box<$base_type> = $crate::__alloc::boxed::Box::new(Foo(42));
-                assert_eq!(get_val(&base), 42);
+#[cfg(feature = "const-type-id")]
+struct TypeIdOf<T: ?Sized>(__std::marker::PhantomData<T>);
 
-                // Try sequential downcasts.
-                if let Some(foo) = base.downcast_ref::<Foo>() {
-                    assert_eq!(foo.0, 42);
-                } else if let Some(bar) = base.downcast_ref::<Bar>() {
-                    assert_eq!(bar.0, 42.0);
-                }
+#[cfg(feature = "const-type-id")]
+#[allow(unknown_lints, clippy::incompatible_msrv)] // The feature documents the Rust version it needs.
+impl<T: Any + ?Sized> TypeIdOf<T> {
+    const ID: TypeId = TypeId::of::<T>();
+}
 
-                set_val(&mut base, 6*9);
-                assert_eq!(get_val(&base), 6*9);
+/// Returns a reference to the object within the trait object if it is of type `T`, or `None` if
+/// it isn't.
+///
+/// Equivalent to the `downcast_ref` method generated by `impl_downcast!`, but cannot be shadowed
+/// by other methods of the same name, e.g. when called from macro-generated code. Like that
+/// method, the returned reference keeps the lifetime of `obj`, so a trait object borrowed from an
+/// arena (or any other long-lived owner) downcasts to a reference that lives as long as the arena.
+pub fn try_ref<B: Downcast + ?Sized, T: Any>(obj: &B) -> Option<&T> {
+    Downcast::as_any(obj).downcast_ref::<T>()
+}
+
+/// Returns a mutable reference to the object within the trait object if it is of type `T`, or
+/// `None` if it isn't. The free counterpart to the generated `downcast_mut` like `try_ref`, and
+/// likewise keeping the lifetime of `obj`.
+pub fn try_mut<B: Downcast + ?Sized, T: Any>(obj: &mut B) -> Option<&mut T> {
+    Downcast::as_any_mut(obj).downcast_mut::<T>()
+}
+
+/// Returns true if the object within the trait object is of type `T`.
+///
+/// Like `downcast_ref` and `downcast_mut`, this works on any `Downcast` trait object, whether or
+/// not `impl_downcast!` was invoked on its trait, e.g. in generic code where the methods it
+/// generates cannot be named.
+pub fn is<B: Downcast + ?Sized, T: Any>(obj: &B) -> bool {
+    Downcast::as_any(obj).is::<T>()
+}
+
+/// `try_ref` under the name of the generated method.
+pub use try_ref as downcast_ref;
+
+/// `try_mut` under the name of the generated method.
+pub use try_mut as downcast_mut;
+
+/// Returns a boxed object from a boxed trait object if the underlying object is of type `T`, or
+/// the original boxed trait object if it isn't. The free counterpart to the generated `downcast`
+/// like `try_ref`, which it implements.
+///
+/// Unlike checking `is` before `Any::downcast`, this compares `TypeId`s only once (see
+/// `benches/downcast.rs`).
+#[allow(unsafe_code)]
+pub fn try_box<B: Downcast + ?Sized, T: Any>(obj: Box<B>) -> Result<Box<T>, Box<B>> {
+    if Downcast::as_any(&*obj).type_id() == __type_id::<T>() {
+        // SAFETY: The object was just checked to be of type `T`, so the data pointer of the box
+        // points to a `T` allocated with its layout, as `Any::downcast` assumes likewise.
+        Ok(unsafe { Box::from_raw(Box::into_raw(obj) as *mut T) })
+    } else {
+        Err(obj)
+    }
+}
+
+/// Returns a reference to the object within the boxed trait object if it is of type `T`, or
+/// `None` if it isn't.
+///
+/// This is equivalent to `boxed.downcast_ref::<T>()` but makes it explicit that only a shared
+/// reborrow of the box is taken. Given a `&mut Box<dyn Trait>`, the mutable borrow can be used
+/// again as soon as the returned reference is no longer needed.
+#[allow(clippy::borrowed_box)]
+pub fn peek_downcast_ref<T: Any, B: Downcast + ?Sized>(boxed: &Box<B>) -> Option<&T> {
+    // Deref explicitly: `Box<B>` is itself `Any` and would otherwise be downcast instead.
+    Downcast::as_any(&**boxed).downcast_ref::<T>()
+}
+
+/// Returns a reference to the first object of type `T` among the boxed trait objects, or `None`
+/// if there is none.
+///
+/// Accepts anything that can be viewed as a slice of boxed trait objects, e.g. `Vec<Box<B>>`,
+/// `[Box<B>; N]` or a borrowed or owned `Cow<[Box<B>]>`.
+pub fn find_downcast_ref<T, B, S>(items: &S) -> Option<&T>
+where
+    T: Any,
+    B: Downcast + ?Sized,
+    S: AsRef<[Box<B>]> + ?Sized,
+{
+    items.as_ref().iter().find_map(|item| Downcast::as_any(&**item).downcast_ref::<T>())
+}
+
+/// Downcasts every boxed trait object into a `Box<T>`, collecting them into `C` (e.g. a
+/// `Vec<Box<T>>`), or returns the first boxed trait object that isn't of type `T`.
+///
+/// Intended for building homogeneous collections; iteration stops at the first mismatch.
+pub fn collect_downcast<T, B, C, I>(items: I) -> Result<C, Box<B>>
+where
+    T: Any,
+    B: Downcast + ?Sized,
+    C: __std::iter::FromIterator<Box<T>>,
+    I: IntoIterator<Item = Box<B>>,
+{
+    items.into_iter().map(|item| {
+        if Downcast::as_any(&*item).is::<T>() {
+            Ok(Downcast::into_any(item).downcast::<T>().unwrap())
+        } else {
+            Err(item)
+        }
+    }).collect()
+}
+
+/// Downcasts every boxed trait object of `items` into a `Box<T>` if all of them are of type `T`.
+/// Otherwise, returns the index of the first one that isn't along with the untouched `items`.
+///
+/// Unlike `collect_downcast`, which drops the objects on a mismatch, this checks all the types
+/// before converting anything, so the caller can report the offending index and keep the objects.
+#[allow(clippy::type_complexity)]
+pub fn downcast_vec_indexed<T, B>(items: Vec<Box<B>>) -> Result<Vec<Box<T>>, (usize, Vec<Box<B>>)>
+where
+    T: Any,
+    B: Downcast + ?Sized,
+{
+    match items.iter().position(|item| !Downcast::as_any(&**item).is::<T>()) {
+        Some(index) => Err((index, items)),
+        None => Ok(items.into_iter().map(|item| Downcast::into_any(item).downcast::<T>().unwrap()).collect()),
+    }
+}
+
+/// Separates boxed trait objects of type `T` from the others in a single pass, keeping the order
+/// within each part.
+pub fn split_downcast<T, B, I>(items: I) -> (Vec<Box<T>>, Vec<Box<B>>)
+where
+    T: Any,
+    B: Downcast + ?Sized,
+    I: IntoIterator<Item = Box<B>>,
+{
+    let mut matches = Vec::new();
+    let mut others = Vec::new();
+    for item in items {
+        if Downcast::as_any(&*item).is::<T>() {
+            matches.push(Downcast::into_any(item).downcast::<T>().unwrap());
+        } else {
+            others.push(item);
+        }
+    }
+    (matches, others)
+}
+
+/// Removes the first boxed trait object of type `T` from `items` and returns it downcast, or
+/// returns `None` if there is none. The remaining objects keep their order.
+pub fn remove_first_of_type<T: Any, B: Downcast + ?Sized>(items: &mut Vec<Box<B>>) -> Option<Box<T>> {
+    let pos = items.iter().position(|item| Downcast::as_any(&**item).is::<T>())?;
+    Some(Downcast::into_any(items.remove(pos)).downcast::<T>().unwrap())
+}
+
+/// Returns the `TypeId` of the concrete type shared by all the boxed trait objects, or `None` if
+/// their types differ or there are none, e.g. to check that a batch can be processed as one type.
+pub fn homogeneous_type_id<B: Downcast + ?Sized>(items: &[Box<B>]) -> Option<TypeId> {
+    let mut ids = items.iter().map(|item| Downcast::as_any(&**item).type_id());
+    let first = ids.next()?;
+    if ids.all(|id| id == first) { Some(first) } else { None }
+}
+
+/// Returns references to the objects of a fixed-size array of boxed trait objects if all of them
+/// are of type `T`, or `None` if any isn't. Requires the `const-generics` feature (and Rust 1.63).
+#[cfg(feature = "const-generics")]
+#[allow(unknown_lints, clippy::incompatible_msrv)] // The feature documents the Rust version it needs.
+pub fn downcast_array_ref<T, B, const N: usize>(arr: &[Box<B>; N]) -> Option<[&T; N]>
+where
+    T: Any,
+    B: Downcast + ?Sized,
+{
+    if !arr.iter().all(|item| Downcast::as_any(&**item).is::<T>()) {
+        return None;
+    }
+    Some(__std::array::from_fn(|i| Downcast::as_any(&*arr[i]).downcast_ref::<T>().expect("checked above")))
+}
+
+/// Returns an iterator downcasting each of the boxed trait objects in turn, yielding `Some` for
+/// objects of type `T` and `None` for others.
+///
+/// Unlike filtering, this keeps the position of each object, e.g. for zipping with other data.
+pub fn downcast_ref_iter<'a, T, B>(items: &'a [Box<B>]) -> impl Iterator<Item = Option<&'a T>> + 'a
+where
+    T: Any,
+    B: Downcast + ?Sized,
+{
+    items.iter().map(|item| Downcast::as_any(&**item).downcast_ref::<T>())
+}
+
+/// Adds methods to iterators over trait objects, keeping only the objects of a given type `T` and
+/// downcasting them, e.g. `plugins.iter().map(|p| &**p).filter_downcast_ref::<Foo>()` in place of
+/// `plugins.iter().filter_map(|p| p.downcast_ref::<Foo>())`.
+///
+/// Unlike the `downcast_ref_iter` function, which keeps the position of each object, these skip
+/// the objects of other types. They apply to iterators over `&dyn Trait`, `&mut dyn Trait` and
+/// `Box<dyn Trait>` respectively, where `impl_downcast!` was invoked on `Trait` and `T` implements
+/// it. In particular, iterators over `&Box<dyn Trait>` are rejected rather than downcasting the
+/// boxes themselves.
+pub trait DowncastIteratorExt: Iterator + Sized {
+    /// Yields the objects of type `T` behind the `&dyn Trait`s of this iterator.
+    fn filter_downcast_ref<T: Any>(self) -> FilterDowncastRef<Self, T> {
+        FilterDowncastRef { iter: self, marker: PhantomData }
+    }
+
+    /// Yields the objects of type `T` behind the `&mut dyn Trait`s of this iterator.
+    fn filter_downcast_mut<T: Any>(self) -> FilterDowncastMut<Self, T> {
+        FilterDowncastMut { iter: self, marker: PhantomData }
+    }
+
+    /// Yields the boxed objects of type `T` among the `Box<dyn Trait>`s of this iterator, dropping
+    /// the others.
+    fn filter_downcast_box<T: Any>(self) -> FilterDowncastBox<Self, T> {
+        FilterDowncastBox { iter: self, marker: PhantomData }
+    }
+}
+
+impl<I: Iterator> DowncastIteratorExt for I {}
+
+/// Iterator returned by `DowncastIteratorExt::filter_downcast_ref`.
+pub struct FilterDowncastRef<I, T> {
+    iter: I,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<'a, I, B, T> Iterator for FilterDowncastRef<I, T>
+where
+    I: Iterator<Item = &'a B>,
+    B: Downcast + Upcast<T> + ?Sized + 'a,
+    T: Any,
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        self.iter.by_ref().filter_map(|obj| Downcast::as_any(obj).downcast_ref::<T>()).next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.iter.size_hint().1)
+    }
+}
+
+/// Iterator returned by `DowncastIteratorExt::filter_downcast_mut`.
+pub struct FilterDowncastMut<I, T> {
+    iter: I,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<'a, I, B, T> Iterator for FilterDowncastMut<I, T>
+where
+    I: Iterator<Item = &'a mut B>,
+    B: Downcast + Upcast<T> + ?Sized + 'a,
+    T: Any,
+{
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<&'a mut T> {
+        self.iter.by_ref().filter_map(|obj| Downcast::as_any_mut(obj).downcast_mut::<T>()).next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.iter.size_hint().1)
+    }
+}
+
+/// Iterator returned by `DowncastIteratorExt::filter_downcast_box`.
+pub struct FilterDowncastBox<I, T> {
+    iter: I,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<I, B, T> Iterator for FilterDowncastBox<I, T>
+where
+    I: Iterator<Item = Box<B>>,
+    B: Downcast + Upcast<T> + ?Sized,
+    T: Any,
+{
+    type Item = Box<T>;
+
+    fn next(&mut self) -> Option<Box<T>> {
+        self.iter.by_ref().filter_map(|obj| try_box(obj).ok()).next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, self.iter.size_hint().1)
+    }
+}
+
+/// Iterator over the descendants of type `T` of a node in a tree of trait objects, in depth-first
+/// pre-order, as returned by `descendants_of_type`, generated by `impl_downcast!` with
+/// `extras => [descendants_of_type]`. The root itself isn't visited.
+///
+/// Rather than collecting the tree, it only keeps the position within each ancestor's children
+/// of the node last visited.
+pub struct DescendantsOfType<'a, B: ?Sized + 'a, T, F> {
+    stack: Vec<__std::slice::Iter<'a, Box<B>>>,
+    children: F,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<'a, B, T, F> DescendantsOfType<'a, B, T, F>
+where
+    B: Downcast + Upcast<T> + ?Sized,
+    T: Any,
+    F: Fn(&'a B) -> &'a [Box<B>],
+{
+    /// Creates an iterator over the descendants of `root`, where `children` returns the children
+    /// of a node.
+    pub fn new(root: &'a B, children: F) -> Self {
+        let stack = __std::iter::once(children(root).iter()).collect();
+        DescendantsOfType { stack, children, marker: PhantomData }
+    }
+}
+
+impl<'a, B, T, F> Iterator for DescendantsOfType<'a, B, T, F>
+where
+    B: Downcast + Upcast<T> + ?Sized,
+    T: Any,
+    F: Fn(&'a B) -> &'a [Box<B>],
+{
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        loop {
+            let node: &'a B = match self.stack.last_mut()?.next() {
+                Some(node) => node,
+                None => {
+                    self.stack.pop();
+                    continue;
+                }
+            };
+            self.stack.push((self.children)(node).iter());
+            if let Some(obj) = Downcast::as_any(node).downcast_ref::<T>() {
+                return Some(obj);
+            }
+        }
+    }
+}
+
+/// Returns a reference to the object within the trait object stored in a `static` `OnceLock` if
+/// it is set and of type `T`, or `None` otherwise.
+///
+/// This never initializes `cell`, and the returned reference keeps the `'static` lifetime, so it
+/// can be handed out freely, e.g. by a global plugin registry. Requires the `once-lock` feature
+/// (and Rust 1.70).
+#[cfg(feature = "once-lock")]
+#[allow(unknown_lints, clippy::incompatible_msrv)] // The feature documents the Rust version it needs.
+pub fn downcast_static<T, B>(cell: &'static __std::sync::OnceLock<Box<B>>) -> Option<&'static T>
+where
+    T: Any,
+    B: Downcast + ?Sized,
+{
+    Downcast::as_any(&**cell.get()?).downcast_ref::<T>()
+}
+
+#[cfg(feature = "once-lock")]
+type Capabilities = __std::sync::Mutex<BTreeMap<TypeId, Vec<Box<dyn Any + Send + Sync>>>>;
+
+#[cfg(feature = "once-lock")]
+#[allow(unknown_lints, clippy::incompatible_msrv)] // The feature documents the Rust version it needs.
+fn capabilities() -> &'static Capabilities {
+    static CAPABILITIES: __std::sync::OnceLock<Capabilities> = __std::sync::OnceLock::new();
+    CAPABILITIES.get_or_init(Default::default)
+}
+
+/// Registers `cap` as a capability of the concrete type `T`, which the `has_capability` method
+/// generated by `impl_downcast!` with `extras => [has_capability]` then reports for trait objects
+/// of type `T`, e.g. in a plugin system that queries features without knowing the concrete types.
+///
+/// Capabilities can be of any type, typically an enum, and are compared by equality. Requires the
+/// `once-lock` feature (and Rust 1.70).
+#[cfg(feature = "once-lock")]
+pub fn register_capability<T: Any, C: Any + PartialEq + Send + Sync>(cap: C) {
+    let mut caps = capabilities().lock().unwrap_or_else(__std::sync::PoisonError::into_inner);
+    let caps = caps.entry(TypeId::of::<T>()).or_default();
+    if !caps.iter().any(|c| c.downcast_ref::<C>() == Some(&cap)) {
+        caps.push(Box::new(cap));
+    }
+}
+
+/// Returns true if `cap` was registered for the concrete type with id `type_id`. Used by the
+/// `has_capability` method generated by `impl_downcast!`.
+#[cfg(feature = "once-lock")]
+#[doc(hidden)]
+pub fn __has_capability<C: Any + PartialEq>(type_id: TypeId, cap: &C) -> bool {
+    let caps = capabilities().lock().unwrap_or_else(__std::sync::PoisonError::into_inner);
+    caps.get(&type_id).map_or(false, |caps| caps.iter().any(|c| c.downcast_ref::<C>() == Some(cap)))
+}
+
+/// Fails the build with an error naming `$extra`, an extra of `impl_downcast!` that requires the
+/// `once-lock` feature, when that feature is disabled.
+#[cfg(feature = "once-lock")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __requires_once_lock {
+    ($extra:ident) => {};
+}
+
+/// Fails the build with an error naming `$extra`, an extra of `impl_downcast!` that requires the
+/// `once-lock` feature, when that feature is disabled.
+#[cfg(not(feature = "once-lock"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __requires_once_lock {
+    ($extra:ident) => {
+        compile_error!(concat!(
+            "the `", stringify!($extra), "` extra of `impl_downcast!` requires the `once-lock` feature of `downcast-rs`"
+        ));
+    };
+}
+
+#[cfg(feature = "once-lock")]
+#[allow(unknown_lints, clippy::incompatible_msrv)] // The feature documents the Rust version it needs.
+fn type_codes() -> &'static __std::sync::Mutex<BTreeMap<TypeId, u32>> {
+    static TYPE_CODES: __std::sync::OnceLock<__std::sync::Mutex<BTreeMap<TypeId, u32>>> =
+        __std::sync::OnceLock::new();
+    TYPE_CODES.get_or_init(Default::default)
+}
+
+/// Registers the concrete type `T` and returns its code, which the `concrete_type_code` method
+/// generated by `impl_downcast!` with `extras => [concrete_type_code]` then reports for trait
+/// objects of type `T`, e.g. to tag objects in a compact binary format.
+///
+/// Codes are assigned sequentially from 0 in the order in which types are first registered;
+/// registering a type again returns its existing code. Since the order may differ between builds
+/// (or runs, if types are registered from several threads), register types in a fixed order at
+/// startup if the codes are persisted. Requires the `once-lock` feature (and Rust 1.70).
+#[cfg(feature = "once-lock")]
+pub fn register_type_code<T: Any>() -> u32 {
+    let mut codes = type_codes().lock().unwrap_or_else(__std::sync::PoisonError::into_inner);
+    let next = codes.len() as u32;
+    *codes.entry(TypeId::of::<T>()).or_insert(next)
+}
+
+/// Returns the code registered for the concrete type with id `type_id`. Used by the
+/// `concrete_type_code` method generated by `impl_downcast!`.
+#[cfg(feature = "once-lock")]
+#[doc(hidden)]
+pub fn __type_code(type_id: TypeId) -> Option<u32> {
+    let codes = type_codes().lock().unwrap_or_else(__std::sync::PoisonError::into_inner);
+    codes.get(&type_id).cloned()
+}
+
+/// Upgrades a `Weak` reference to a trait object and downcasts the resulting `Rc` to type `T`.
+///
+/// Returns `None` if the object was already dropped or isn't of type `T`.
+pub fn upgrade_downcast<T: Any, B: Downcast + ?Sized>(weak: &Weak<B>) -> Option<Rc<T>> {
+    Downcast::into_any_rc(weak.upgrade()?).downcast::<T>().ok()
+}
+
+/// Replaces the object within the boxed trait object by `f` applied to it if it is of type `T`,
+/// returning whether it was.
+///
+/// The object is moved out and passed to `f` by value, and the result is moved back in place
+/// without reallocating. Since `T` needn't implement `Default`, `placeholder` provides the value
+/// standing in for the object meanwhile; it is only called if the object is of type `T`. Should
+/// `f` panic, the placeholder is left in the box, which thus always holds a valid object.
+pub fn downcast_modify<T, B, P, F>(slot: &mut Box<B>, placeholder: P, f: F) -> bool
+where
+    T: Any,
+    B: Downcast + ?Sized,
+    P: FnOnce() -> T,
+    F: FnOnce(T) -> T,
+{
+    match Downcast::as_any_mut(&mut **slot).downcast_mut::<T>() {
+        Some(obj) => {
+            let value = __std::mem::replace(obj, placeholder());
+            *obj = f(value);
+            true
+        }
+        None => false,
+    }
+}
+
+/// The error returned by the `try_downcast*` methods generated by `impl_downcast!` with `extras`
+/// when the trait object does not wrap the requested type.
+///
+/// It records the names of both the requested and the actual concrete type and hands back the
+/// original value (e.g. the `Box<dyn Trait>` for `try_downcast`), which is `()` for the borrowing
+/// variants, along with the `DowncastErrorKind` of the failure. It is `Send`, `Sync` and
+/// `'static` whenever the value is, e.g. for traits extending `DowncastSync`, so that it converts
+/// into a `Box<dyn Error + Send + Sync>`.
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate downcast_rs;
+/// # use downcast_rs::Downcast;
+/// trait Shape: Downcast {}
+/// impl_downcast!(Shape extras => [try_downcast]);
+/// struct Circle(f32);
+/// impl Shape for Circle {}
+/// struct Square;
+/// impl Shape for Square {}
+///
+/// # fn main() {
+/// let shape: Box<dyn Shape> = Box::new(Circle(1.0));
+/// let err = shape.try_downcast::<Square>().map(|_| ()).unwrap_err();
+/// assert_eq!(err.to_string(), format!("cannot downcast `{}` to `{}`",
+///     err.actual_type_name(), err.expected_type_name()));
+/// // The original box is handed back intact.
+/// assert_eq!(err.into_inner().downcast_ref::<Circle>().unwrap().0, 1.0);
+/// # }
+/// ```
+pub struct DowncastError<T = ()> {
+    value: T,
+    expected: &'static str,
+    actual: &'static str,
+    kind: DowncastErrorKind,
+}
+
+/// The reason a downcast failed, as returned by `DowncastError::kind`.
+///
+/// More kinds may be added, so matches on it should have a wildcard arm.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[allow(unknown_lints, clippy::manual_non_exhaustive)]
+pub enum DowncastErrorKind {
+    /// The trait object wraps a type other than the requested one, whose name differs.
+    TypeMismatch,
+    /// The trait object wraps a type other than the requested one, but of the same name, which
+    /// makes the names useless for telling them apart. E.g. both are the same type from two
+    /// versions of a crate, or closures defined in the same function.
+    NameCollision,
+    // Stands in for `#[non_exhaustive]`, which requires Rust 1.40.
+    #[doc(hidden)]
+    __NonExhaustive,
+}
+
+impl<T> DowncastError<T> {
+    /// Creates an error for a failed downcast of `value` (whose concrete type is named `actual`)
+    /// into the type named `expected`.
+    pub fn new(value: T, expected: &'static str, actual: &'static str) -> Self {
+        let kind = if expected == actual {
+            DowncastErrorKind::NameCollision
+        } else {
+            DowncastErrorKind::TypeMismatch
+        };
+        DowncastError { value, expected, actual, kind }
+    }
+    /// Returns why the downcast failed.
+    pub fn kind(&self) -> DowncastErrorKind { self.kind }
+    /// Returns the name of the type that was requested.
+    pub fn expected_type_name(&self) -> &'static str { self.expected }
+    /// Returns the name of the concrete type actually wrapped by the trait object.
+    pub fn actual_type_name(&self) -> &'static str { self.actual }
+    /// Recovers the original value that failed to downcast.
+    pub fn into_inner(self) -> T { self.value }
+}
+
+impl<T> fmt::Debug for DowncastError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("DowncastError")
+            .field("kind", &self.kind)
+            .field("expected", &self.expected)
+            .field("actual", &self.actual)
+            .finish()
+    }
+}
+
+impl<T> fmt::Display for DowncastError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "cannot downcast `{}` to `{}`", self.actual, self.expected)?;
+        if self.kind == DowncastErrorKind::NameCollision {
+            f.write_str(", a distinct type of the same name")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T> __std::error::Error for DowncastError<T> {}
+
+/// A boxed trait object tagged with the `TypeId` of its concrete type, as returned by the `tag`
+/// method generated by `impl_downcast!` with `extras => [tag]`.
+///
+/// E.g. a receiver of a channel of tagged messages can branch on `type_id` once and downcast
+/// only in the matching branch, rather than calling `is` for each candidate type.
+pub struct TaggedMessage<B: ?Sized> {
+    type_id: TypeId,
+    obj: Box<B>,
+}
+
+impl<B: Downcast + ?Sized> TaggedMessage<B> {
+    /// Tags `obj` with the `TypeId` of its concrete type.
+    pub fn new(obj: Box<B>) -> Self {
+        TaggedMessage { type_id: Downcast::as_any(&*obj).type_id(), obj }
+    }
+    /// Returns the `TypeId` of the object's concrete type.
+    pub fn type_id(&self) -> TypeId { self.type_id }
+    /// Returns true if the object is of type `T`.
+    pub fn is<T: Any>(&self) -> bool { self.type_id == TypeId::of::<T>() }
+    /// Returns the boxed object if it is of type `T`, or the original message if it isn't.
+    pub fn downcast<T: Any>(self) -> Result<Box<T>, Self> {
+        if self.is::<T>() {
+            Ok(Downcast::into_any(self.obj).downcast::<T>().unwrap())
+        } else {
+            Err(self)
+        }
+    }
+    /// Returns the boxed trait object, dropping the tag.
+    pub fn into_inner(self) -> Box<B> { self.obj }
+}
+
+/// The outcome of `downcast_owned_or_keep`, generated by `impl_downcast!` with
+/// `extras => [downcast_owned_or_keep]`: either the boxed object of the requested type or the
+/// original boxed trait object.
+///
+/// It carries the same information as the `Result` returned by `downcast`, with variant names that
+/// read better in large `match` trees where neither outcome is an error.
+pub enum DowncastOutcome<T, B: ?Sized> {
+    /// The object was of the requested type.
+    Concrete(Box<T>),
+    /// The object was of another type and is handed back unchanged.
+    Original(Box<B>),
+}
+
+/// Converts boxed objects of type `T` into boxed trait objects. `impl_downcast!` implements it on
+/// the trait object for every type implementing the trait.
+///
+/// This lets generic code such as `DowncastMap` box up concrete objects, which would otherwise
+/// require the unstable `CoerceUnsized`.
+pub trait Upcast<T> {
+    /// Converts `Box<T>` into `Box<dyn Trait>`.
+    fn upcast(obj: Box<T>) -> Box<Self>;
+}
+
+/// A map storing at most one boxed trait object per concrete type, e.g. for the components of an
+/// entity.
+///
+/// Objects are looked up by their concrete type and handed out downcast to it. They are kept
+/// sorted by the `TypeId` of their type, so iterating over the map is deterministic within a run of
+/// the program. `TypeId`s, hence their order, may however change between compilations, so the
+/// order mustn't be persisted or compared between builds.
+pub struct DowncastMap<B: ?Sized> {
+    map: BTreeMap<TypeId, Box<B>>,
+}
+
+impl<B: Downcast + ?Sized> DowncastMap<B> {
+    /// Creates an empty map.
+    pub fn new() -> Self {
+        DowncastMap { map: BTreeMap::new() }
+    }
+    /// Returns the number of objects in the map.
+    pub fn len(&self) -> usize { self.map.len() }
+    /// Returns true if the map holds no objects.
+    pub fn is_empty(&self) -> bool { self.map.is_empty() }
+    /// Returns true if the map holds an object of type `T`.
+    pub fn contains<T: Any>(&self) -> bool { self.map.contains_key(&TypeId::of::<T>()) }
+    /// Inserts `value`, returning the previously stored object of type `T`, if any.
+    pub fn insert<T: Any>(&mut self, value: T) -> Option<T> where B: Upcast<T> {
+        self.map.insert(TypeId::of::<T>(), B::upcast(Box::new(value))).map(Self::unwrap_box)
+    }
+    /// Removes and returns the object of type `T`, if any.
+    pub fn remove<T: Any>(&mut self) -> Option<T> {
+        self.map.remove(&TypeId::of::<T>()).map(Self::unwrap_box)
+    }
+    /// Returns a reference to the object of type `T`, if any.
+    pub fn get<T: Any>(&self) -> Option<&T> {
+        self.map.get(&TypeId::of::<T>()).and_then(|obj| Downcast::as_any(&**obj).downcast_ref::<T>())
+    }
+    /// Returns a mutable reference to the object of type `T`, if any.
+    pub fn get_mut<T: Any>(&mut self) -> Option<&mut T> {
+        self.map.get_mut(&TypeId::of::<T>()).and_then(|obj| Downcast::as_any_mut(&mut **obj).downcast_mut::<T>())
+    }
+    /// Returns a mutable reference to the object of type `T`, inserting the result of `f` first if
+    /// there is none.
+    pub fn get_or_insert_with<T: Any, F: FnOnce() -> T>(&mut self, f: F) -> &mut T where B: Upcast<T> {
+        self.entry::<T>().or_insert_with(f)
+    }
+    /// Returns the entry for the object of type `T` for in-place manipulation.
+    pub fn entry<'a, T: Any>(&'a mut self) -> DowncastMapEntry<'a, B, T> {
+        DowncastMapEntry { entry: self.map.entry(TypeId::of::<T>()), marker: PhantomData }
+    }
+    /// Returns an iterator over the stored trait objects, in `TypeId` order.
+    pub fn values<'a>(&'a self) -> impl Iterator<Item = &'a B> + 'a {
+        self.map.values().map(|obj| &**obj)
+    }
+    /// Returns an iterator over the stored trait objects along with the `TypeId`s of their
+    /// concrete types, in `TypeId` order.
+    pub fn iter<'a>(&'a self) -> impl Iterator<Item = (TypeId, &'a B)> + 'a {
+        self.map.iter().map(|(&type_id, obj)| (type_id, &**obj))
+    }
+
+    fn unwrap_box<T: Any>(obj: Box<B>) -> T {
+        // Objects are keyed by their concrete type, so this cannot fail.
+        match Downcast::into_any(obj).downcast::<T>() {
+            Ok(obj) => *obj,
+            Err(_) => unreachable!(),
+        }
+    }
+}
+
+impl<B: Downcast + ?Sized> Default for DowncastMap<B> {
+    fn default() -> Self { DowncastMap::new() }
+}
+
+/// The entry for the object of type `T` in a `DowncastMap`, as returned by `DowncastMap::entry`.
+pub struct DowncastMapEntry<'a, B: ?Sized + 'a, T> {
+    entry: btree_map::Entry<'a, TypeId, Box<B>>,
+    marker: PhantomData<T>,
+}
+
+impl<'a, B: Downcast + ?Sized, T: Any> DowncastMapEntry<'a, B, T> {
+    /// Returns true if the map holds an object of type `T`.
+    pub fn is_occupied(&self) -> bool {
+        match self.entry {
+            btree_map::Entry::Occupied(_) => true,
+            btree_map::Entry::Vacant(_) => false,
+        }
+    }
+    /// Calls `f` on the object of type `T` if there is one.
+    pub fn and_modify<F: FnOnce(&mut T)>(mut self, f: F) -> Self {
+        if let btree_map::Entry::Occupied(ref mut entry) = self.entry {
+            f(Self::downcast(entry.get_mut()));
+        }
+        self
+    }
+    /// Returns a mutable reference to the object of type `T`, inserting `value` first if there is
+    /// none.
+    pub fn or_insert(self, value: T) -> &'a mut T where B: Upcast<T> {
+        self.or_insert_with(|| value)
+    }
+    /// Returns a mutable reference to the object of type `T`, inserting the result of `f` first if
+    /// there is none.
+    pub fn or_insert_with<F: FnOnce() -> T>(self, f: F) -> &'a mut T where B: Upcast<T> {
+        let obj = match self.entry {
+            btree_map::Entry::Occupied(entry) => entry.into_mut(),
+            btree_map::Entry::Vacant(entry) => entry.insert(B::upcast(Box::new(f()))),
+        };
+        Self::downcast(obj)
+    }
+    /// Returns a mutable reference to the object of type `T`, inserting `T::default()` first if
+    /// there is none.
+    pub fn or_default(self) -> &'a mut T where B: Upcast<T>, T: Default {
+        self.or_insert_with(T::default)
+    }
+
+    fn downcast(obj: &mut Box<B>) -> &mut T {
+        // Objects are keyed by their concrete type, so this cannot fail.
+        Downcast::as_any_mut(&mut **obj).downcast_mut::<T>().unwrap()
+    }
+}
+
+/// Converts a pinned boxed trait object into a pinned box of type `T` if the underlying object is
+/// of type `T`. Used by the `downcast_pin` method generated by `impl_downcast!` with `unchecked`.
+#[doc(hidden)]
+#[allow(unsafe_code)]
+pub fn __downcast_pin<T: Any, B: Downcast + ?Sized>(pinned: Pin<Box<B>>) -> Result<Pin<Box<T>>, Pin<Box<B>>> {
+    if !Downcast::as_any(&*pinned).is::<T>() {
+        return Err(pinned);
+    }
+    // SAFETY: The `&mut B` only serves to take back ownership of the allocation below, and the
+    // object is never moved out of it: `Box::from_raw` and `Any::downcast` merely convert the
+    // pointer, and the box is pinned again before anything else can observe it. Pinning the
+    // object thus continues uninterrupted at the same address until it is dropped in place.
+    // (`Pin::into_inner_unchecked` would need Rust 1.39.)
+    let mut pinned = pinned;
+    let ptr: *mut B = unsafe { pinned.as_mut().get_unchecked_mut() };
+    __std::mem::forget(pinned);
+    // SAFETY: `ptr` comes from the box forgotten above, which no longer owns the allocation.
+    let boxed = unsafe { Box::from_raw(ptr) };
+    Ok(Pin::from(Downcast::into_any(boxed).downcast::<T>().unwrap()))
+}
+
+/// Returns a pinned reference to the object within the pinned trait object if it is of type `T`.
+/// Used by the `downcast_pin_ref` method generated by `impl_downcast!` with `unchecked`.
+#[doc(hidden)]
+#[allow(unsafe_code)]
+pub fn __downcast_pin_ref<T: Any, B: Downcast + ?Sized>(pinned: Pin<&B>) -> Option<Pin<&T>> {
+    let obj = Downcast::as_any(pinned.get_ref()).downcast_ref::<T>()?;
+    // SAFETY: `obj` is the very object `pinned` points to, viewed at its concrete type rather
+    // than projected to one of its fields, so the pinning guarantee of `pinned` covers it. Its
+    // `Drop` impl is that of the object, which must already respect pinning.
+    Some(unsafe { Pin::new_unchecked(obj) })
+}
+
+/// Returns a pinned mutable reference to the object within the pinned trait object if it is of
+/// type `T`. Used by the `downcast_pin_mut` method generated by `impl_downcast!` with `unchecked`.
+#[doc(hidden)]
+#[allow(unsafe_code)]
+pub fn __downcast_pin_mut<T: Any, B: Downcast + ?Sized>(pinned: Pin<&mut B>) -> Option<Pin<&mut T>> {
+    // SAFETY: The `&mut B` is only used to view the object at its concrete type, which doesn't
+    // move it, and is pinned again right away below; it never reaches safe code unpinned.
+    let obj = Downcast::as_any_mut(unsafe { pinned.get_unchecked_mut() }).downcast_mut::<T>()?;
+    // SAFETY: `obj` is the very object `pinned` points to, viewed at its concrete type rather
+    // than projected to one of its fields, so it is still pinned. Unless `T: Unpin`, safe code
+    // can only move it through the returned `Pin`, which forbids that.
+    Some(unsafe { Pin::new_unchecked(obj) })
+}
+
+/// Converts a boxed trait object into a box of type `T` without checking its concrete type. Used by
+/// the `downcast_unchecked` method generated by `impl_downcast!`.
+///
+/// # Safety
+///
+/// The concrete type of the object must be exactly `T`.
+#[doc(hidden)]
+#[allow(unsafe_code)]
+pub unsafe fn __downcast_unchecked<T: Any, B: Downcast + ?Sized>(obj: Box<B>) -> Box<T> {
+    debug_assert!(Downcast::as_any(&*obj).is::<T>());
+    Box::from_raw(Box::into_raw(obj) as *mut T)
+}
+
+/// Views a trait object as an object of type `T` without checking its concrete type. Used by the
+/// `downcast_ref_unchecked` method generated by `impl_downcast!`.
+///
+/// # Safety
+///
+/// The concrete type of the object must be exactly `T`.
+#[doc(hidden)]
+#[allow(unsafe_code)]
+pub unsafe fn __downcast_ref_unchecked<T: Any, B: Downcast + ?Sized>(obj: &B) -> &T {
+    debug_assert!(Downcast::as_any(obj).is::<T>());
+    // `Any::downcast_ref_unchecked` is unstable.
+    &*(obj as *const B as *const T)
+}
+
+/// Views a trait object as an object of type `T` without checking its concrete type. Used by the
+/// `downcast_mut_unchecked` method generated by `impl_downcast!`.
+///
+/// # Safety
+///
+/// The concrete type of the object must be exactly `T`.
+#[doc(hidden)]
+#[allow(unsafe_code)]
+pub unsafe fn __downcast_mut_unchecked<T: Any, B: Downcast + ?Sized>(obj: &mut B) -> &mut T {
+    debug_assert!(Downcast::as_any(obj).is::<T>());
+    &mut *(obj as *mut B as *mut T)
+}
+
+/// A trait object viewed as `&dyn Any` along with its `TypeId`, which is read through the vtable
+/// only once. Used by `dispatch_downcast!` to compare that `TypeId` against each listed type
+/// without re-checking it when downcasting.
+#[doc(hidden)]
+pub struct __Dispatch<'a> {
+    any: &'a dyn Any,
+    id: TypeId,
+}
+
+#[doc(hidden)]
+impl<'a> __Dispatch<'a> {
+    pub fn new<B: Downcast + ?Sized>(obj: &'a B) -> Self {
+        let any = Downcast::as_any(obj);
+        __Dispatch { any, id: any.type_id() }
+    }
+
+    pub fn is<T: Any>(&self) -> bool {
+        self.id == __type_id::<T>()
+    }
+
+    #[allow(unsafe_code)]
+    pub fn get<T: Any>(&self) -> Option<&'a T> {
+        if self.is::<T>() {
+            // SAFETY: The fields are private and `id` was read from `any` itself, so `any` points
+            // to a `T`.
+            Some(unsafe { &*(self.any as *const dyn Any as *const T) })
+        } else {
+            None
+        }
+    }
+}
+
+/// Adds downcasting support to traits that extend `downcast::Downcast` by defining forwarding
+/// methods to the corresponding implementations on `std::any::Any` in the standard library.
+///
+/// See <https://users.rust-lang.org/t/how-to-create-a-macro-to-impl-a-provided-type-parametrized-trait/5289>
+/// for why this is implemented this way to support templatized traits.
+///
+/// Besides the inherent downcasting methods on `dyn Trait`, it implements `Upcast<T>` on
+/// `dyn Trait` for every `T: Trait`.
+///
+/// The generated code contains no `unsafe` code unless the `unchecked` option is given, so it can
+/// be used in crates with `#![forbid(unsafe_code)]`.
+///
+/// The trait may also be named by a path, e.g. `impl_downcast!(crate::types::Base<T>)`, whether
+/// relative (to `crate`, `self`, `super` or an imported module) or global (starting with `::`).
+///
+/// The lists of parameters and associated types may end with a trailing comma, e.g.
+/// `impl_downcast!(Base<T, U,> assoc G, H,)`.
+///
+/// For traits extending `DowncastSend`, the invocation may start with `send`, e.g.
+/// `impl_downcast!(send Base)`, which checks that the trait does. Its trait objects then offer
+/// `into_any_send`, converting a `Box<dyn Trait>` into a `Box<dyn Any + Send>`. `downcast` already
+/// keeps `Send`: it hands back the `Box<dyn Trait>` on failure, which is `Send` since the trait is.
+///
+/// Several traits may be covered by a single invocation by separating them with semicolons, e.g.
+/// `impl_downcast! { A; B<T>; sync C assoc H where H: Copy; }`, each accepting the full syntax.
+///
+/// Type parameters may be bounded inline, e.g. `impl_downcast!(Base<T: Copy, U: Clone>)`, which
+/// is equivalent to `impl_downcast!(Base<T, U> where T: Copy, U: Clone)`. A `where` clause may
+/// still follow for the remaining predicates. Likewise, associated types may be bounded inline,
+/// e.g. `impl_downcast!(Base assoc G: Copy, H: Clone)`.
+///
+/// Lifetime parameters, e.g. `impl_downcast!(Base<'a, T>)`, are bounded by `'static` in the
+/// generated impls since `Any` requires it, so the methods are only available on
+/// `dyn Base<'static, T>`.
+///
+/// # Options
+///
+/// The trait (and its `where` clause, if any) may be followed by options generating additional
+/// methods for a fixed list of concrete types:
+///
+/// - `clone => [Foo, Bar]`: `clone_box(&self) -> Option<Box<Self>>` clones the object if it is
+///   one of the listed types.
+/// - `clone => BaseClone`: `clone_box(&self) -> Box<Self>` clones the object of any concrete
+///   type. It generates a trait `BaseClone`, generic over the same parameters as the impls, which
+///   is implemented for every `Clone` implementor of the trait; add it as a supertrait, e.g.
+///   `trait Base<T>: Downcast + BaseClone<T>`, to require every implementor to be `Clone`. Traits
+///   with associated types are not supported, as such a supertrait would refer to `Self`.
+/// - `from_any => [Foo, Bar]`: `from_any(Box<dyn Any>) -> Result<Box<Self>, Box<dyn Any>>`
+///   converts a `Box<dyn Any>` holding one of the listed types into a boxed trait object.
+/// - `selector => [Foo, Bar]`: generates a `TargetSelector` enum with a variant per listed type
+///   and `downcast_selected(&self, TargetSelector) -> Option<&dyn Any>`, which returns the object
+///   if it is of the selected type. Use `selector Name => [...]` to name the enum differently.
+/// - `order => [Foo, Bar]`: `nth_type_ref(&self, n: usize) -> Option<&dyn Any>` returns the
+///   object if its type is the `n`th listed one, e.g. for dispatching on the index of a type in a
+///   canonical order.
+/// - `header Header => [(Foo, |foo| &foo.header), (Bar, |bar| &bar.hdr)]`:
+///   `common_header(&self) -> Option<&Header>` returns the field shared by the listed types, each
+///   projected by its own closure, or `None` if the object is of another type.
+/// - `kind => [Foo, Bar]`: generates a `TypeKind` enum with a variant per listed type plus
+///   `Unknown`, and `kind(&self) -> TypeKind`, which returns the variant of the object's type with
+///   a single `TypeId` lookup. Use `kind Name => [...]` to name the enum differently, and
+///   `kind non_exhaustive => [...]` to add a hidden variant (as `#[non_exhaustive]` requires Rust
+///   1.40) so that matches on it need a `_` arm and the list can grow without breaking them.
+/// - `ref_enum Name => [Foo, Bar]`: generates an enum `Name<'a, B>` with a variant holding a
+///   `&'a` reference per listed type plus `Other(&'a B)`, and `downcast_union(&self) -> Name<'_,
+///   Self>`, which borrows the object as the variant of its type for matching.
+/// - `owned_enum Name => [Foo, Bar]`: generates an enum `Name<B>` with a variant holding a `Box`
+///   per listed type plus `Other(Box<B>)`, and `From<Box<dyn Trait>>` for `Name<dyn Trait>`, which
+///   moves the object into the variant of its type. The conversion cannot fail, so `TryFrom`
+///   follows with `Error = Infallible`.
+/// - `try_from => [Foo, Bar]`: implements `TryFrom<Box<dyn Trait>>` for `Box<Foo>` and `Box<Bar>`
+///   with `Error = Box<dyn Trait>`, delegating to `downcast`, for generic code written against the
+///   standard conversion traits. The orphan rules forbid a blanket impl for every `Box<T>` with
+///   `T: Trait`, hence the list.
+/// - `predicates => [Foo, Bar]`: `type_predicates() -> &'static [fn(&Self) -> bool]` returns the
+///   `is` predicates of the listed types, in order, e.g. for branch-predictable batch dispatch.
+/// - `extras => [name, ...]`: generates the less common methods named in the list, which are left
+///   out otherwise. As inherent methods of `dyn Trait`, they would make calls to methods of the
+///   same names of the trait or its supertraits ambiguous, and shadow those of other traits
+///   implemented for the trait object:
+///   - `type_name(&self) -> &'static str` returns the name of the object's concrete type, for
+///     diagnostics.
+///   - `concrete_layout(&self) -> Layout` returns the layout of the object's concrete type.
+///   - `concrete_type_id(&self) -> TypeId` returns the `TypeId` of the object's concrete type.
+///   - `downcast_inline(self: Box<Self>) -> Result<T, Box<Self>>` moves the object out of the
+///     box.
+///   - `map_downcast(self: Box<Self>, f: F) -> Box<Self>` applies `f` to the boxed object if it
+///     is of type `T`.
+///   - `downcast_rc_or_clone(self: Rc<Self>) -> Result<Rc<T>, Rc<Self>>` downcasts into a uniquely owned
+///     `Rc`, cloning the object if it is shared.
+///   - `downcast_weak(weak: Weak<Self>) -> Result<Weak<T>, Weak<Self>>` and, for `sync` traits,
+///     `downcast_weak_arc` downcast `Weak` references, called as
+///     `<dyn Trait>::downcast_weak::<T>(weak)`.
+///   - `downcast_ref_of(obj: &Self) -> Option<&T>` is `downcast_ref` called as
+///     `<dyn Trait>::downcast_ref_of::<T>(obj)`.
+///   - `try_downcast`, `try_downcast_ref` and `try_downcast_mut` are `downcast`, `downcast_ref`
+///     and `downcast_mut` returning a `DowncastError` naming both types on failure.
+///   - `project(&self, f: F) -> Option<&U>` returns what `f` projects out of the object,
+///     typically after downcasting it to one of several types.
+///   - `tag(self: Box<Self>) -> TaggedMessage<Self>` tags the boxed object with the
+///     `TypeId` of its concrete type.
+///   - `descendants_of_type(&self, children: F) -> DescendantsOfType<..>` iterates over the
+///     descendants of type `T` in a tree of trait objects, given the children of each node.
+///   - `has_capability(&self, cap: C) -> bool` returns true if `cap` was registered for the
+///     object's concrete type with `register_capability`. Requires the `once-lock` feature.
+///   - `concrete_type_code(&self) -> Option<u32>` returns the code registered for the object's
+///     concrete type with `register_type_code`. Requires the `once-lock` feature.
+///   - `downcast_ref_addr(&self) -> Option<(&T, usize)>` returns the address of the object
+///     along with the reference to it.
+///   - `downcast_ref_reupcast(&self) -> Option<(&T, fn(&T) -> &Self)>` returns a function
+///     converting the downcast reference back into a trait object reference along with it.
+///   - `downcast_owned_or_keep(self: Box<Self>) -> DowncastOutcome<T, Self>` is `downcast`
+///     with variant names for `match` trees where neither outcome is an error.
+///   - `assert_concrete(&self)` panics in debug builds if the object isn't of type `T`.
+///   - `downcast_ref_named(&self) -> Result<&T, (&'static str, &'static str)>` returns the names
+///     of `T` and of the object's type if it isn't of type `T`.
+/// - `feature = "name"`: compiles the generated impls and items only if the crate invoking
+///   `impl_downcast!` is built with the feature `name`, making downcasting an optional capability.
+///   The trait itself is unaffected.
+/// - `doc_hidden`: hides the generated methods from the documentation while leaving them callable,
+///   e.g. when downcasting is an implementation detail.
+/// - `no_inline`: marks the generated methods `#[inline]` only in release builds (without
+///   `debug_assertions`), which may speed up debug builds of crates invoking `impl_downcast!` on
+///   many traits.
+/// - `unchecked`: `unsafe fn downcast_unchecked(self: Box<Self>) -> Box<T>`,
+///   `downcast_ref_unchecked(&self) -> &T` and `downcast_mut_unchecked(&mut self) -> &mut T` skip
+///   the type check of their checked counterparts for hot paths where the concrete type is already
+///   known, e.g. from a `TypeId` stored alongside the object. It also generates the pinned
+///   downcasts `downcast_pin(self: Pin<Box<Self>>) -> Result<Pin<Box<T>>, Pin<Box<Self>>>`,
+///   `downcast_pin_ref(self: Pin<&Self>) -> Option<Pin<&T>>` and `downcast_pin_mut(self: Pin<&mut
+///   Self>) -> Option<Pin<&mut T>>`, which are safe to call but project the pin with `unsafe`
+///   code in `downcast-rs`.
+///
+/// For a trait with a single implementor, sealed by extending `SingleImpl<Type>`,
+/// `impl_downcast!(single Trait => Type)` also generates `into_inner(self: Box<Self>) ->
+/// Box<Type>`, which cannot fail.
+///
+/// `downcast` hands back the object in its original box, whereas `downcast_inline` (see `extras`)
+/// moves it out of the box, which copies it. Prefer `downcast` for large types and dereference the box as needed
+/// (see `benches/downcast_inline.rs`):
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate downcast_rs;
+/// # use downcast_rs::Downcast;
+/// trait Base: Downcast {}
+/// impl_downcast!(Base);
+/// struct Image { pixels: [u32; 1 << 16] }
+/// impl Base for Image {}
+///
+/// # fn main() {
+/// let base: Box<dyn Base> = Box::new(Image { pixels: [0; 1 << 16] });
+/// // Only the pointer is moved.
+/// let image: Box<Image> = base.downcast::<Image>().map_err(|_| "not an image").unwrap();
+/// assert_eq!(image.pixels.len(), 1 << 16);
+/// # }
+/// ```
+///
+/// `Rc` and `Arc` are not `#[fundamental]`, so unlike with the `try_from` option for `Box`, the
+/// orphan rules forbid implementing e.g. `TryFrom<Rc<dyn Trait>>` for `Rc<Type>` outside of the
+/// standard library. Use `downcast_rc` and `downcast_arc` instead, which likewise hand back the
+/// original pointer if the types differ.
+///
+/// Trait aliases (`impl_downcast!(alias Alias)`) are rejected since a trait object cannot combine
+/// several non-auto traits; invoke `impl_downcast!` on the underlying trait instead.
+///
+/// A trait that isn't object-safe is rejected with an error at the `impl_downcast!` invocation,
+/// ahead of the errors about the methods generated on its trait object.
+///
+/// A concrete associated type given as `impl Trait` is rejected; on nightly, name the opaque type
+/// with `type Alias = impl Trait;` and pass `assoc H = Alias` instead.
+#[macro_export(local_inner_macros)]
+macro_rules! impl_downcast {
+    (@impl_full
+        [$($trait_:tt)*] [$($param_types:tt)*]
+        for [$($generics:tt)*]
+        types [$($forall_types:ident)*]
+        where [$($preds:tt)*]
+        opts [$($opts:tt)*]
+        attrs [$($attrs:tt)*]
+        inline [$($inline:tt)*]
+        cfg [$($cfg:tt)*]
+    ) => {
+        impl_downcast! {
+            @assert_object_safe [$($trait_)*] [$($param_types)*]
+                for [$($generics)*] types [$($forall_types)*] where [$($preds)*] cfg [$($cfg)*]
+        }
+        impl_downcast! {
+            @inject_where
+                [
+                    $($attrs)*
+                    #[allow(unknown_lints, unused_parens, clippy::multiple_bound_locations)]
+                    impl<$($generics)*> dyn ($($trait_)*<$($param_types)*>)
+                ]
+                types [$($forall_types),*]
+                where [$($preds)*]
+                [{
+                    impl_downcast! { @impl_body [$($trait_)*] [$($param_types)*] [$($inline)*] }
+                    impl_downcast! { @impl_opts [$($trait_)*] [$($param_types)*] [$($inline)*] $($opts)* }
+                }]
+        }
+        impl_downcast! {
+            @impl_upcast [$($trait_)*] [$($param_types)*]
+                for [$($generics)*] types [$($forall_types)*] where [$($preds)*] attrs [$($attrs)*]
+                inline [$($inline)*]
+        }
+        impl_downcast! {
+            @impl_conv [$($trait_)*] [$($param_types)*]
+                for [$($generics)*] types [$($forall_types)*] where [$($preds)*] attrs [$($attrs)*]
+                $($opts)*
+        }
+        impl_downcast! { @items [$($cfg)*] $($opts)* }
+    };
+
+    (@impl_full_sync
+        [$($trait_:tt)*] [$($param_types:tt)*]
+        for [$($generics:tt)*]
+        types [$($forall_types:ident)*]
+        where [$($preds:tt)*]
+        opts [$($opts:tt)*]
+        attrs [$($attrs:tt)*]
+        inline [$($inline:tt)*]
+        cfg [$($cfg:tt)*]
+    ) => {
+        impl_downcast! {
+            @assert_object_safe [$($trait_)*] [$($param_types)*]
+                for [$($generics)*] types [$($forall_types)*] where [$($preds)*] cfg [$($cfg)*]
+        }
+        impl_downcast! {
+            @inject_where
+                [
+                    $($attrs)*
+                    #[allow(unknown_lints, unused_parens, clippy::multiple_bound_locations)]
+                    impl<$($generics)*> dyn ($($trait_)*<$($param_types)*>)
+                ]
+                types [$($forall_types),*]
+                where [$($preds)*]
+                [{
+                    impl_downcast! { @impl_body [$($trait_)*] [$($param_types)*] [$($inline)*] }
+                    impl_downcast! { @impl_body_sync [$($trait_)*] [$($param_types)*] [$($inline)*] }
+                    impl_downcast! { @impl_opts [$($trait_)*] [$($param_types)*] [$($inline)*] $($opts)* }
+                }]
+        }
+        impl_downcast! {
+            @impl_upcast [$($trait_)*] [$($param_types)*]
+                for [$($generics)*] types [$($forall_types)*] where [$($preds)*] attrs [$($attrs)*]
+                inline [$($inline)*]
+        }
+        impl_downcast! {
+            @impl_conv [$($trait_)*] [$($param_types)*]
+                for [$($generics)*] types [$($forall_types)*] where [$($preds)*] attrs [$($attrs)*]
+                $($opts)*
+        }
+        impl_downcast! { @items [$($cfg)*] $($opts)* }
+    };
+
+    (@impl_full_send
+        [$($trait_:tt)*] [$($param_types:tt)*]
+        for [$($generics:tt)*]
+        types [$($forall_types:ident)*]
+        where [$($preds:tt)*]
+        opts [$($opts:tt)*]
+        attrs [$($attrs:tt)*]
+        inline [$($inline:tt)*]
+        cfg [$($cfg:tt)*]
+    ) => {
+        impl_downcast! {
+            @assert_object_safe [$($trait_)*] [$($param_types)*]
+                for [$($generics)*] types [$($forall_types)*] where [$($preds)*] cfg [$($cfg)*]
+        }
+        impl_downcast! {
+            @inject_where
+                [
+                    $($attrs)*
+                    #[allow(unknown_lints, unused_parens, clippy::multiple_bound_locations)]
+                    impl<$($generics)*> dyn ($($trait_)*<$($param_types)*>)
+                ]
+                types [$($forall_types),*]
+                where [$($preds)*]
+                [{
+                    impl_downcast! { @impl_body [$($trait_)*] [$($param_types)*] [$($inline)*] }
+                    impl_downcast! { @impl_body_send [$($trait_)*] [$($param_types)*] [$($inline)*] }
+                    impl_downcast! { @impl_opts [$($trait_)*] [$($param_types)*] [$($inline)*] $($opts)* }
+                }]
+        }
+        impl_downcast! {
+            @impl_upcast [$($trait_)*] [$($param_types)*]
+                for [$($generics)*] types [$($forall_types)*] where [$($preds)*] attrs [$($attrs)*]
+                inline [$($inline)*]
+        }
+        impl_downcast! {
+            @impl_conv [$($trait_)*] [$($param_types)*]
+                for [$($generics)*] types [$($forall_types)*] where [$($preds)*] attrs [$($attrs)*]
+                $($opts)*
+        }
+        impl_downcast! { @items [$($cfg)*] $($opts)* }
+    };
+
+    // Names the trait object type in a function that is never called, so that a trait that isn't
+    // object-safe fails with "the trait cannot be made into an object" right at the macro
+    // invocation rather than with confusing errors about the generated methods.
+    (@assert_object_safe
+        [$($trait_:tt)*] [$($param_types:tt)*]
+        for [$($generics:tt)*]
+        types [$($forall_types:ident)*]
+        where [$($preds:tt)*]
+        cfg [$($cfg:tt)*]
+    ) => {
+        $($cfg)*
+        const _: () = {
+            impl_downcast! {
+                @inject_where
+                    [
+                        #[allow(dead_code, unknown_lints, unused_parens, clippy::multiple_bound_locations)]
+                        fn __assert_object_safe<$($generics)*>()
+                    ]
+                    types [$($forall_types),*]
+                    where [$($preds)*]
+                    [{
+                        let _: $crate::__std::option::Option<&dyn ($($trait_)*<$($param_types)*>)> =
+                            $crate::__std::option::Option::None;
+                    }]
+            }
+        };
+    };
+
+    (@impl_upcast
+        [$($trait_:tt)*] [$($param_types:tt)*]
+        for [$($generics:tt)*]
+        types [$($forall_types:ident)*]
+        where [$($preds:tt)*]
+        attrs [$($attrs:tt)*]
+        inline [$($inline:tt)*]
+    ) => {
+        impl_downcast! {
+            @inject_where
+                [
+                    $($attrs)*
+                    #[allow(unknown_lints, unused_parens, clippy::multiple_bound_locations)]
+                    impl<$($generics)* __T: $($trait_)*<$($param_types)*>>
+                        $crate::Upcast<__T> for dyn ($($trait_)*<$($param_types)*>)
+                ]
+                types [$($forall_types),*]
+                where [$($preds)*]
+                [{
+                    $($inline)*
+                    fn upcast(obj: $crate::__alloc::boxed::Box<__T>) -> $crate::__alloc::boxed::Box<Self> { obj }
+                }]
+        }
+    };
+
+    // Traits and trait impls requested through options.
+    (@impl_conv $trait_:tt $params:tt for $gen:tt types $types:tt where $preds:tt attrs $attrs:tt) => {};
+    (@impl_conv
+        [$($trait_:tt)*] [$($param_types:tt)*]
+        for [$($generics:tt)*]
+        types [$($forall_types:ident)*]
+        where [$($preds:tt)*]
+        attrs [$($attrs:tt)*]
+        { clone_trait $helper:ident } $($opts:tt)*
+    ) => {
+        impl_downcast! {
+            @clone_trait $helper [] [] [$($generics)*] [$($trait_)*] [$($param_types)*]
+                for [$($generics)*] types [$($forall_types)*] where [$($preds)*] attrs [$($attrs)*]
+        }
+        impl_downcast! {
+            @impl_conv [$($trait_)*] [$($param_types)*]
+                for [$($generics)*] types [$($forall_types)*] where [$($preds)*] attrs [$($attrs)*]
+                $($opts)*
+        }
+    };
+    (@impl_conv
+        [$($trait_:tt)*] [$($param_types:tt)*]
+        for [$($generics:tt)*]
+        types [$($forall_types:ident)*]
+        where [$($preds:tt)*]
+        attrs [$($attrs:tt)*]
+        { owned_enum $name:ident [$($ty:ident),* $(,)*] } $($opts:tt)*
+    ) => {
+        impl_downcast! {
+            @inject_where
+                [
+                    $($attrs)*
+                    #[allow(unknown_lints, unused_parens, clippy::multiple_bound_locations)]
+                    impl<$($generics)*>
+                        $crate::__std::convert::From<$crate::__alloc::boxed::Box<dyn ($($trait_)*<$($param_types)*>)>>
+                        for $name<dyn ($($trait_)*<$($param_types)*>)>
+                ]
+                types [$($forall_types),*]
+                where [$($preds)*]
+                [{
+                    fn from(obj: $crate::__alloc::boxed::Box<dyn ($($trait_)*<$($param_types)*>)>) -> Self {
+                        $(
+                            let obj = match obj.downcast::<$ty>() {
+                                $crate::__std::result::Result::Ok(obj) => return $name::$ty(obj),
+                                $crate::__std::result::Result::Err(obj) => obj,
+                            };
+                        )*
+                        $name::Other(obj)
+                    }
+                }]
+        }
+        impl_downcast! {
+            @impl_conv [$($trait_)*] [$($param_types)*]
+                for [$($generics)*] types [$($forall_types)*] where [$($preds)*] attrs [$($attrs)*]
+                $($opts)*
+        }
+    };
+    (@impl_conv $trait_:tt $params:tt for $gen:tt types $types:tt where $preds:tt attrs $attrs:tt
+        { try_from [] } $($opts:tt)*
+    ) => {
+        impl_downcast! { @impl_conv $trait_ $params for $gen types $types where $preds attrs $attrs $($opts)* }
+    };
+    (@impl_conv $trait_:tt $params:tt for $gen:tt types $types:tt where $preds:tt attrs $attrs:tt
+        { try_from [$ty:ty] } $($opts:tt)*
+    ) => {
+        impl_downcast! { @impl_try_from $trait_ $params for $gen types $types where $preds attrs $attrs $ty }
+        impl_downcast! { @impl_conv $trait_ $params for $gen types $types where $preds attrs $attrs $($opts)* }
+    };
+    (@impl_conv $trait_:tt $params:tt for $gen:tt types $types:tt where $preds:tt attrs $attrs:tt
+        { try_from [$ty:ty, $($tys:tt)*] } $($opts:tt)*
+    ) => {
+        impl_downcast! { @impl_try_from $trait_ $params for $gen types $types where $preds attrs $attrs $ty }
+        impl_downcast! {
+            @impl_conv $trait_ $params for $gen types $types where $preds attrs $attrs
+                { try_from [$($tys)*] } $($opts)*
+        }
+    };
+    (@impl_conv $trait_:tt $params:tt for $gen:tt types $types:tt where $preds:tt attrs $attrs:tt
+        $opt:tt $($opts:tt)*
+    ) => {
+        impl_downcast! { @impl_conv $trait_ $params for $gen types $types where $preds attrs $attrs $($opts)* }
+    };
+
+    // The helper trait of `clone => ...`, generic over the impl's generic parameters, which are
+    // first collected without the `'static` bounds of lifetimes as its parameters and by name as
+    // its arguments. The clone is boxed up in an impl for each concrete type, reached through the
+    // trait object's vtable as the helper is a supertrait, rather than by patching pointers.
+    (@clone_trait $helper:ident [$($decl:tt)*] [$($args:tt)*] [$lt:lifetime : 'static, $($gen:tt)*] $($rest:tt)*) => {
+        impl_downcast! { @clone_trait $helper [$($decl)* $lt,] [$($args)* $lt,] [$($gen)*] $($rest)* }
+    };
+    (@clone_trait $helper:ident [$($decl:tt)*] [$($args:tt)*] [const $name:ident : $cty:ty, $($gen:tt)*] $($rest:tt)*) => {
+        impl_downcast! { @clone_trait $helper [$($decl)* const $name: $cty,] [$($args)* $name,] [$($gen)*] $($rest)* }
+    };
+    (@clone_trait $helper:ident [$($decl:tt)*] [$($args:tt)*] [$ty:ident, $($gen:tt)*] $($rest:tt)*) => {
+        impl_downcast! { @clone_trait $helper [$($decl)* $ty,] [$($args)* $ty,] [$($gen)*] $($rest)* }
+    };
+    (@clone_trait $helper:ident [$($decl:tt)*] [$($args:tt)*] []
+        [$($trait_:tt)*] [$($param_types:tt)*]
+        for [$($generics:tt)*]
+        types [$($forall_types:ident)*]
+        where [$($preds:tt)*]
+        attrs [$($attrs:tt)*]
+    ) => {
+        impl_downcast! {
+            @inject_where
+                [
+                    $($attrs)*
+                    /// Clones objects of `Clone` types into boxed trait objects. Generated by the
+                    /// `clone => ...` option of `impl_downcast!` for use as a supertrait.
+                    #[allow(unknown_lints, unused_parens, clippy::multiple_bound_locations)]
+                    pub trait $helper<$($decl)*>
+                ]
+                types []
+                where [$($preds)*]
+                [{
+                    #[doc(hidden)]
+                    fn __clone_box(&self) -> $crate::__alloc::boxed::Box<dyn ($($trait_)*<$($param_types)*>)>;
+                }]
+        }
+        impl_downcast! {
+            @inject_where
+                [
+                    $($attrs)*
+                    #[allow(unknown_lints, unused_parens, clippy::multiple_bound_locations)]
+                    impl<$($generics)* __T: $crate::__std::clone::Clone + $($trait_)*<$($param_types)*>>
+                        $helper<$($args)*> for __T
+                ]
+                types [$($forall_types),*]
+                where [$($preds)*]
+                [{
+                    fn __clone_box(&self) -> $crate::__alloc::boxed::Box<dyn ($($trait_)*<$($param_types)*>)> {
+                        $crate::__alloc::boxed::Box::new($crate::__std::clone::Clone::clone(self))
+                    }
+                }]
+        }
+    };
+
+    (@impl_try_from
+        [$($trait_:tt)*] [$($param_types:tt)*]
+        for [$($generics:tt)*]
+        types [$($forall_types:ident)*]
+        where [$($preds:tt)*]
+        attrs [$($attrs:tt)*]
+        $ty:ty
+    ) => {
+        impl_downcast! {
+            @inject_where
+                [
+                    $($attrs)*
+                    #[allow(unknown_lints, unused_parens, clippy::multiple_bound_locations)]
+                    impl<$($generics)*>
+                        $crate::__std::convert::TryFrom<$crate::__alloc::boxed::Box<dyn ($($trait_)*<$($param_types)*>)>>
+                        for $crate::__alloc::boxed::Box<$ty>
+                ]
+                types [$($forall_types),*]
+                where [$($preds)*]
+                [{
+                    type Error = $crate::__alloc::boxed::Box<dyn ($($trait_)*<$($param_types)*>)>;
+                    fn try_from(
+                        obj: $crate::__alloc::boxed::Box<dyn ($($trait_)*<$($param_types)*>)>
+                    ) -> $crate::__std::result::Result<Self, Self::Error> {
+                        obj.downcast::<$ty>()
+                    }
+                }]
+        }
+    };
+
+    (@impl_body [$($trait_:tt)*] [$($types:tt)*] [$($inline:tt)*]) => {
+        /// Returns true if the trait object wraps an object of type `__T`. This reads the object's
+        /// `TypeId` through its vtable, so it cannot be called in const contexts.
+        $($inline)*
+        pub fn is<__T: $($trait_)*<$($types)*>>(&self) -> bool {
+            $crate::Downcast::as_any(self).type_id() == $crate::__type_id::<__T>()
+        }
+        /// Returns a boxed object from a boxed trait object if the underlying object is of type
+        /// `__T`. Returns the original boxed trait if it isn't. Either way, the object is neither
+        /// moved nor reallocated. The `try_downcast` extra also names both types on failure.
+        $($inline)*
+        pub fn downcast<__T: $($trait_)*<$($types)*>>(
+            self: $crate::__alloc::boxed::Box<Self>
+        ) -> $crate::__std::result::Result<$crate::__alloc::boxed::Box<__T>, $crate::__alloc::boxed::Box<Self>> {
+            $crate::try_box(self)
+        }
+        /// Returns an `Rc`-ed object from an `Rc`-ed trait object if the underlying object is of
+        /// type `__T`. Returns the original `Rc`-ed trait if it isn't.
+        $($inline)*
+        pub fn downcast_rc<__T: $($trait_)*<$($types)*>>(
+            self: $crate::__alloc::rc::Rc<Self>
+        ) -> $crate::__std::result::Result<$crate::__alloc::rc::Rc<__T>, $crate::__alloc::rc::Rc<Self>> {
+            if self.is::<__T>() {
+                Ok($crate::Downcast::into_any_rc(self).downcast::<__T>().unwrap())
+            } else {
+                Err(self)
+            }
+        }
+        /// Returns a reference to the object within the trait object if it is of type `__T`, or
+        /// `None` if it isn't. `__T` needn't be `Sync`, so any interior mutability (e.g. a `Cell`)
+        /// remains usable through the returned reference.
+        $($inline)*
+        pub fn downcast_ref<__T: $($trait_)*<$($types)*>>(&self) -> $crate::__std::option::Option<&__T> {
+            $crate::Downcast::as_any(self).downcast_ref::<__T>()
+        }
+        /// Returns a mutable reference to the object within the trait object if it is of type
+        /// `__T`, or `None` if it isn't.
+        $($inline)*
+        pub fn downcast_mut<__T: $($trait_)*<$($types)*>>(&mut self) -> $crate::__std::option::Option<&mut __T> {
+            $crate::Downcast::as_any_mut(self).downcast_mut::<__T>()
+        }
+    };
+
+    (@impl_body_send [$($trait_:tt)*] [$($types:tt)*] [$($inline:tt)*]) => {
+        // `into_any_send` itself is reached through `DowncastSend` on the trait object: an
+        // inherent method of the same name would make calls to it ambiguous. This only checks that
+        // the trait extends `DowncastSend`, so that `send` is rejected on traits that don't.
+        #[allow(dead_code)]
+        fn __assert_downcast_send(
+            self: $crate::__alloc::boxed::Box<Self>
+        ) -> $crate::__alloc::boxed::Box<dyn $crate::__std::any::Any + $crate::__std::marker::Send> {
+            $crate::DowncastSend::into_any_send(self)
+        }
+    };
+
+    (@impl_body_sync [$($trait_:tt)*] [$($types:tt)*] [$($inline:tt)*]) => {
+        /// Returns an `Arc`-ed object from an `Arc`-ed trait object if the underlying object is of
+        /// type `__T`. Returns the original `Arc`-ed trait if it isn't.
+        $($inline)*
+        pub fn downcast_arc<__T: $($trait_)*<$($types)*> + $crate::__std::any::Any + $crate::__std::marker::Send + $crate::__std::marker::Sync>(
+            self: $crate::__alloc::sync::Arc<Self>,
+        ) -> $crate::__std::result::Result<$crate::__alloc::sync::Arc<__T>, $crate::__alloc::sync::Arc<Self>>
+        {
+            if self.is::<__T>() {
+                Ok($crate::DowncastSync::into_any_arc(self).downcast::<__T>().unwrap())
+            } else {
+                Err(self)
+            }
+        }
+    };
+
+    // Methods requested through options following the trait.
+    (@impl_opts $trait_:tt $params:tt [$($inline:tt)*]) => {};
+    (@impl_opts $trait_:tt $params:tt [$($inline:tt)*] { doc_hidden } $($opts:tt)*) => {
+        impl_downcast! { @impl_opts $trait_ $params [$($inline)*] $($opts)* }
+    };
+    (@impl_opts $trait_:tt $params:tt [$($inline:tt)*] { no_inline } $($opts:tt)*) => {
+        impl_downcast! { @impl_opts $trait_ $params [$($inline)*] $($opts)* }
+    };
+    (@impl_opts $trait_:tt $params:tt [$($inline:tt)*] { feature $feature:tt } $($opts:tt)*) => {
+        impl_downcast! { @impl_opts $trait_ $params [$($inline)*] $($opts)* }
+    };
+    (@impl_opts [$($trait_:tt)*] [$($types:tt)*] [$($inline:tt)*] { unchecked } $($opts:tt)*) => {
+        /// Returns a boxed object from a boxed trait object without checking that the underlying
+        /// object is of type `__T`. The object is neither moved nor reallocated.
+        ///
+        /// # Safety
+        ///
+        /// The concrete type of the object must be exactly `__T`, e.g. as established earlier by
+        /// `is` or by a tag stored alongside the object. Otherwise, the behavior is undefined.
+        $($inline)*
+        #[allow(unused_unsafe)]
+        pub unsafe fn downcast_unchecked<__T: $($trait_)*<$($types)*>>(
+            self: $crate::__alloc::boxed::Box<Self>
+        ) -> $crate::__alloc::boxed::Box<__T> {
+            unsafe { $crate::__downcast_unchecked(self) }
+        }
+        /// Returns a reference to the object within the trait object without checking that it is
+        /// of type `__T`.
+        ///
+        /// # Safety
+        ///
+        /// The concrete type of the object must be exactly `__T`, e.g. as established earlier by
+        /// `is` or by a tag stored alongside the object. Otherwise, the behavior is undefined.
+        $($inline)*
+        #[allow(unused_unsafe)]
+        pub unsafe fn downcast_ref_unchecked<__T: $($trait_)*<$($types)*>>(&self) -> &__T {
+            unsafe { $crate::__downcast_ref_unchecked(self) }
+        }
+        /// Returns a mutable reference to the object within the trait object without checking
+        /// that it is of type `__T`.
+        ///
+        /// # Safety
+        ///
+        /// The concrete type of the object must be exactly `__T`, e.g. as established earlier by
+        /// `is` or by a tag stored alongside the object. Otherwise, the behavior is undefined.
+        $($inline)*
+        #[allow(unused_unsafe)]
+        pub unsafe fn downcast_mut_unchecked<__T: $($trait_)*<$($types)*>>(&mut self) -> &mut __T {
+            unsafe { $crate::__downcast_mut_unchecked(self) }
+        }
+        /// Returns a pinned boxed object from a pinned boxed trait object (e.g. from `Box::pin`)
+        /// if the underlying object is of type `__T`. Returns the original pinned boxed trait if
+        /// it isn't. Either way, the object stays pinned in place.
+        $($inline)*
+        pub fn downcast_pin<__T: $($trait_)*<$($types)*>>(
+            self: $crate::__std::pin::Pin<$crate::__alloc::boxed::Box<Self>>
+        ) -> $crate::__std::result::Result<
+            $crate::__std::pin::Pin<$crate::__alloc::boxed::Box<__T>>,
+            $crate::__std::pin::Pin<$crate::__alloc::boxed::Box<Self>>,
+        > {
+            $crate::__downcast_pin(self)
+        }
+        /// Returns a pinned reference to the object within the pinned trait object if it is of
+        /// type `__T`, or `None` if it isn't, e.g. for `pinned.as_ref()` on a `Pin<Box<dyn Trait>>`.
+        /// The object stays where it is, so it remains pinned even if `__T` is not `Unpin`.
+        $($inline)*
+        pub fn downcast_pin_ref<__T: $($trait_)*<$($types)*>>(
+            self: $crate::__std::pin::Pin<&Self>
+        ) -> $crate::__std::option::Option<$crate::__std::pin::Pin<&__T>> {
+            $crate::__downcast_pin_ref(self)
+        }
+        /// Returns a pinned mutable reference to the object within the pinned trait object if it
+        /// is of type `__T`, or `None` if it isn't, e.g. for a `Pin<&mut dyn Trait>` field
+        /// projected out of a pinned struct as `#[pin_project]` does. The object stays where it
+        /// is, so it remains pinned even if `__T` is not `Unpin`.
+        $($inline)*
+        pub fn downcast_pin_mut<__T: $($trait_)*<$($types)*>>(
+            self: $crate::__std::pin::Pin<&mut Self>
+        ) -> $crate::__std::option::Option<$crate::__std::pin::Pin<&mut __T>> {
+            $crate::__downcast_pin_mut(self)
+        }
+        impl_downcast! { @impl_opts [$($trait_)*] [$($types)*] [$($inline)*] $($opts)* }
+    };
+    (@impl_opts $trait_:tt $params:tt [$($inline:tt)*] { owned_enum $($opt:tt)* } $($opts:tt)*) => {
+        impl_downcast! { @impl_opts $trait_ $params [$($inline)*] $($opts)* }
+    };
+    (@impl_opts $trait_:tt $params:tt [$($inline:tt)*] { try_from $($opt:tt)* } $($opts:tt)*) => {
+        impl_downcast! { @impl_opts $trait_ $params [$($inline)*] $($opts)* }
+    };
+    (@impl_opts $trait_:tt $params:tt [$($inline:tt)*] { clone [$($ty:ty),* $(,)*] } $($opts:tt)*) => {
+        /// Returns a boxed clone of the object if its concrete type is one of the types listed in
+        /// `clone => [...]`, or `None` if it isn't.
+        $($inline)*
+        pub fn clone_box(&self) -> $crate::__std::option::Option<$crate::__alloc::boxed::Box<Self>> {
+            $(
+                if let $crate::__std::option::Option::Some(obj) = self.downcast_ref::<$ty>() {
+                    let cloned: $crate::__alloc::boxed::Box<Self> =
+                        $crate::__alloc::boxed::Box::new($crate::__std::clone::Clone::clone(obj));
+                    return $crate::__std::option::Option::Some(cloned);
+                }
+            )*
+            $crate::__std::option::Option::None
+        }
+        impl_downcast! { @impl_opts $trait_ $params [$($inline)*] $($opts)* }
+    };
+
+    (@impl_opts $trait_:tt $params:tt [$($inline:tt)*] { clone_trait $helper:ident } $($opts:tt)*) => {
+        /// Returns a boxed clone of the object, whose concrete type is `Clone` as required by the
+        /// supertrait generated by the `clone => ...` option.
+        $($inline)*
+        pub fn clone_box(&self) -> $crate::__alloc::boxed::Box<Self> {
+            self.__clone_box()
+        }
+        impl_downcast! { @impl_opts $trait_ $params [$($inline)*] $($opts)* }
+    };
+    (@impl_opts $trait_:tt $params:tt [$($inline:tt)*] { from_any [$($ty:ty),* $(,)*] } $($opts:tt)*) => {
+        /// Converts a `Box<dyn Any>` into a boxed trait object if its concrete type is one of the
+        /// types listed in `from_any => [...]`. Returns the original box if it isn't.
+        $($inline)*
+        pub fn from_any(
+            any: $crate::__alloc::boxed::Box<dyn $crate::__std::any::Any>
+        ) -> $crate::__std::result::Result<$crate::__alloc::boxed::Box<Self>, $crate::__alloc::boxed::Box<dyn $crate::__std::any::Any>> {
+            $(
+                let any = match any.downcast::<$ty>() {
+                    $crate::__std::result::Result::Ok(obj) => {
+                        let obj: $crate::__alloc::boxed::Box<Self> = obj;
+                        return $crate::__std::result::Result::Ok(obj);
+                    }
+                    $crate::__std::result::Result::Err(any) => any,
+                };
+            )*
+            $crate::__std::result::Result::Err(any)
+        }
+        impl_downcast! { @impl_opts $trait_ $params [$($inline)*] $($opts)* }
+    };
+
+    // Less common methods named in `extras => [...]`, one at a time.
+    (@impl_opts $trait_:tt $params:tt [$($inline:tt)*] { extras [] } $($opts:tt)*) => {
+        impl_downcast! { @impl_opts $trait_ $params [$($inline)*] $($opts)* }
+    };
+    (@impl_opts $trait_:tt $params:tt [$($inline:tt)*] { extras [, $($names:tt)*] } $($opts:tt)*) => {
+        impl_downcast! { @impl_opts $trait_ $params [$($inline)*] { extras [$($names)*] } $($opts)* }
+    };
+    (@impl_opts [$($trait_:tt)*] [$($types:tt)*] [$($inline:tt)*] { extras [type_name $($names:tt)*] } $($opts:tt)*) => {
+        /// Returns the name of the object's concrete type as given by `core::any::type_name`,
+        /// intended for diagnostics only. Called on a `Box<dyn Trait>`, it names the object's type
+        /// rather than the `Box`.
+        $($inline)*
+        pub fn type_name(&self) -> &'static str {
+            $crate::Downcast::__type_name(self)
+        }
+        impl_downcast! { @impl_opts [$($trait_)*] [$($types)*] [$($inline)*] { extras [$($names)*] } $($opts)* }
+    };
+    (@impl_opts [$($trait_:tt)*] [$($types:tt)*] [$($inline:tt)*] { extras [concrete_layout $($names:tt)*] } $($opts:tt)*) => {
+        /// Returns the layout (size and alignment) of the object's concrete type, e.g. for
+        /// allocator accounting. Called on a `Box<dyn Trait>`, it describes the object rather than
+        /// the `Box`.
+        $($inline)*
+        pub fn concrete_layout(&self) -> $crate::__std::alloc::Layout {
+            $crate::Downcast::__concrete_layout(self)
+        }
+        impl_downcast! { @impl_opts [$($trait_)*] [$($types)*] [$($inline)*] { extras [$($names)*] } $($opts)* }
+    };
+    (@impl_opts [$($trait_:tt)*] [$($types:tt)*] [$($inline:tt)*] { extras [concrete_type_id $($names:tt)*] } $($opts:tt)*) => {
+        /// Returns the `TypeId` of the object's concrete type, e.g. to key a map of handlers,
+        /// without having to import `Downcast`.
+        $($inline)*
+        pub fn concrete_type_id(&self) -> $crate::__std::any::TypeId {
+            $crate::__std::any::Any::type_id($crate::Downcast::as_any(self))
+        }
+        impl_downcast! { @impl_opts [$($trait_)*] [$($types)*] [$($inline)*] { extras [$($names)*] } $($opts)* }
+    };
+    (@impl_opts [$($trait_:tt)*] [$($types:tt)*] [$($inline:tt)*] { extras [downcast_inline $($names:tt)*] } $($opts:tt)*) => {
+        /// Moves the object out of a boxed trait object, freeing the box, if it is of type `__T`.
+        /// Returns the original boxed trait if it isn't.
+        $($inline)*
+        pub fn downcast_inline<__T: $($trait_)*<$($types)*>>(
+            self: $crate::__alloc::boxed::Box<Self>
+        ) -> $crate::__std::result::Result<__T, $crate::__alloc::boxed::Box<Self>> {
+            self.downcast::<__T>().map(|obj| *obj)
+        }
+        impl_downcast! { @impl_opts [$($trait_)*] [$($types)*] [$($inline)*] { extras [$($names)*] } $($opts)* }
+    };
+    (@impl_opts [$($trait_:tt)*] [$($types:tt)*] [$($inline:tt)*] { extras [map_downcast $($names:tt)*] } $($opts:tt)*) => {
+        /// Applies `f` to the boxed object if it is of type `__T` and boxes the result back up as a
+        /// trait object. Returns the original boxed trait if it isn't of type `__T`.
+        $($inline)*
+        pub fn map_downcast<__T, __F>(self: $crate::__alloc::boxed::Box<Self>, f: __F) -> $crate::__alloc::boxed::Box<Self>
+        where
+            __T: $($trait_)*<$($types)*>,
+            __F: $crate::__std::ops::FnOnce($crate::__alloc::boxed::Box<__T>) -> $crate::__alloc::boxed::Box<__T>,
+        {
+            match self.downcast::<__T>() {
+                Ok(obj) => f(obj),
+                Err(this) => this,
+            }
+        }
+        impl_downcast! { @impl_opts [$($trait_)*] [$($types)*] [$($inline)*] { extras [$($names)*] } $($opts)* }
+    };
+    (@impl_opts [$($trait_:tt)*] [$($types:tt)*] [$($inline:tt)*] { extras [downcast_rc_or_clone $($names:tt)*] } $($opts:tt)*) => {
+        /// Returns a uniquely owned `Rc`-ed object from an `Rc`-ed trait object if the underlying
+        /// object is of type `__T`. Returns the original `Rc`-ed trait object if it isn't. If the
+        /// object is shared with other `Rc`s or `Weak`s, it is cloned into a new `Rc`, leaving the
+        /// others untouched.
+        $($inline)*
+        pub fn downcast_rc_or_clone<__T: $($trait_)*<$($types)*> + $crate::__std::clone::Clone>(
+            self: $crate::__alloc::rc::Rc<Self>
+        ) -> $crate::__std::result::Result<$crate::__alloc::rc::Rc<__T>, $crate::__alloc::rc::Rc<Self>> {
+            let mut rc = self.downcast_rc::<__T>()?;
+            if $crate::__alloc::rc::Rc::get_mut(&mut rc).is_none() {
+                rc = $crate::__alloc::rc::Rc::new((*rc).clone());
+            }
+            $crate::__std::result::Result::Ok(rc)
+        }
+        impl_downcast! { @impl_opts [$($trait_)*] [$($types)*] [$($inline)*] { extras [$($names)*] } $($opts)* }
+    };
+    (@impl_opts [$($trait_:tt)*] [$($types:tt)*] [$($inline:tt)*] { extras [downcast_weak $($names:tt)*] } $($opts:tt)*) => {
+        /// Returns a `Weak` reference to the object from a `Weak` reference to the trait object if
+        /// the object is alive and of type `__T`. Returns the original `Weak` if it isn't, and also
+        /// if the object was already dropped, since its type is then unknown.
+        ///
+        /// Since `Weak` cannot be a method receiver on stable Rust, call it as
+        /// `<dyn Trait>::downcast_weak::<Type>(weak)`. The object is briefly upgraded to check its
+        /// type, so calling this doesn't require an `Rc` but does keep the object alive for that
+        /// long.
+        $($inline)*
+        pub fn downcast_weak<__T: $($trait_)*<$($types)*>>(
+            weak: $crate::__alloc::rc::Weak<Self>
+        ) -> $crate::__std::result::Result<$crate::__alloc::rc::Weak<__T>, $crate::__alloc::rc::Weak<Self>> {
+            match weak.upgrade().map(|rc| rc.downcast_rc::<__T>()) {
+                $crate::__std::option::Option::Some($crate::__std::result::Result::Ok(rc)) =>
+                    $crate::__std::result::Result::Ok($crate::__alloc::rc::Rc::downgrade(&rc)),
+                _ => $crate::__std::result::Result::Err(weak),
+            }
+        }
+        impl_downcast! { @impl_opts [$($trait_)*] [$($types)*] [$($inline)*] { extras [$($names)*] } $($opts)* }
+    };
+    (@impl_opts [$($trait_:tt)*] [$($types:tt)*] [$($inline:tt)*] { extras [downcast_weak_arc $($names:tt)*] } $($opts:tt)*) => {
+        /// Returns a `Weak` reference to the object from an `Arc`'s `Weak` reference to the trait
+        /// object if the object is alive and of type `__T`. Returns the original `Weak` if it
+        /// isn't, and also if the object was already dropped, since its type is then unknown.
+        ///
+        /// Call it as `<dyn Trait>::downcast_weak_arc::<Type>(weak)`, like `downcast_weak`. The
+        /// object is briefly upgraded to check its type; should all other `Arc`s be dropped
+        /// meanwhile, it is dropped on this thread.
+        $($inline)*
+        pub fn downcast_weak_arc<__T: $($trait_)*<$($types)*> + $crate::__std::any::Any + $crate::__std::marker::Send + $crate::__std::marker::Sync>(
+            weak: $crate::__alloc::sync::Weak<Self>,
+        ) -> $crate::__std::result::Result<$crate::__alloc::sync::Weak<__T>, $crate::__alloc::sync::Weak<Self>>
+        {
+            match weak.upgrade().map(|arc| arc.downcast_arc::<__T>()) {
+                $crate::__std::option::Option::Some($crate::__std::result::Result::Ok(arc)) =>
+                    $crate::__std::result::Result::Ok($crate::__alloc::sync::Arc::downgrade(&arc)),
+                _ => $crate::__std::result::Result::Err(weak),
+            }
+        }
+        impl_downcast! { @impl_opts [$($trait_)*] [$($types)*] [$($inline)*] { extras [$($names)*] } $($opts)* }
+    };
+    (@impl_opts [$($trait_:tt)*] [$($types:tt)*] [$($inline:tt)*] { extras [downcast_ref_of $($names:tt)*] } $($opts:tt)*) => {
+        /// Returns a reference to the object within the trait object `obj` if it is of type `__T`,
+        /// or `None` if it isn't, with the lifetime of `obj` spelled out.
+        ///
+        /// This is `downcast_ref` called as `<dyn Trait>::downcast_ref_of::<Type>(obj)`, e.g. on
+        /// the result of a trait method returning `&dyn Trait`, for generic code where the
+        /// returned reference must be tied to the lifetime of `obj` rather than to a reborrow.
+        $($inline)*
+        #[allow(clippy::needless_lifetimes)]
+        pub fn downcast_ref_of<'__a, __T: $($trait_)*<$($types)*>>(
+            obj: &'__a Self
+        ) -> $crate::__std::option::Option<&'__a __T> {
+            $crate::Downcast::as_any(obj).downcast_ref::<__T>()
+        }
+        impl_downcast! { @impl_opts [$($trait_)*] [$($types)*] [$($inline)*] { extras [$($names)*] } $($opts)* }
+    };
+    (@impl_opts [$($trait_:tt)*] [$($types:tt)*] [$($inline:tt)*] { extras [try_downcast $($names:tt)*] } $($opts:tt)*) => {
+        /// Returns a boxed object from a boxed trait object if the underlying object is of type
+        /// `__T`. Returns a `DowncastError` holding the original boxed trait if it isn't.
+        $($inline)*
+        pub fn try_downcast<__T: $($trait_)*<$($types)*>>(
+            self: $crate::__alloc::boxed::Box<Self>
+        ) -> $crate::__std::result::Result<$crate::__alloc::boxed::Box<__T>, $crate::DowncastError<$crate::__alloc::boxed::Box<Self>>> {
+            self.downcast::<__T>().map_err(|this| {
+                let actual = $crate::Downcast::__type_name(&*this);
+                $crate::DowncastError::new(this, $crate::__std::any::type_name::<__T>(), actual)
+            })
+        }
+        impl_downcast! { @impl_opts [$($trait_)*] [$($types)*] [$($inline)*] { extras [$($names)*] } $($opts)* }
+    };
+    (@impl_opts [$($trait_:tt)*] [$($types:tt)*] [$($inline:tt)*] { extras [try_downcast_ref $($names:tt)*] } $($opts:tt)*) => {
+        /// Returns a reference to the object within the trait object if it is of type `__T`, or
+        /// a `DowncastError` naming both types if it isn't.
+        $($inline)*
+        pub fn try_downcast_ref<__T: $($trait_)*<$($types)*>>(&self) -> $crate::__std::result::Result<&__T, $crate::DowncastError> {
+            let actual = $crate::Downcast::__type_name(self);
+            self.downcast_ref::<__T>().ok_or_else(|| {
+                $crate::DowncastError::new((), $crate::__std::any::type_name::<__T>(), actual)
+            })
+        }
+        impl_downcast! { @impl_opts [$($trait_)*] [$($types)*] [$($inline)*] { extras [$($names)*] } $($opts)* }
+    };
+    (@impl_opts [$($trait_:tt)*] [$($types:tt)*] [$($inline:tt)*] { extras [try_downcast_mut $($names:tt)*] } $($opts:tt)*) => {
+        /// Returns a mutable reference to the object within the trait object if it is of type
+        /// `__T`, or a `DowncastError` naming both types if it isn't.
+        $($inline)*
+        pub fn try_downcast_mut<__T: $($trait_)*<$($types)*>>(&mut self) -> $crate::__std::result::Result<&mut __T, $crate::DowncastError> {
+            let actual = $crate::Downcast::__type_name(self);
+            self.downcast_mut::<__T>().ok_or_else(|| {
+                $crate::DowncastError::new((), $crate::__std::any::type_name::<__T>(), actual)
+            })
+        }
+        impl_downcast! { @impl_opts [$($trait_)*] [$($types)*] [$($inline)*] { extras [$($names)*] } $($opts)* }
+    };
+    (@impl_opts $trait_:tt $params:tt [$($inline:tt)*] { extras [project $($names:tt)*] } $($opts:tt)*) => {
+        /// Returns what `f` projects out of the trait object, typically after downcasting it to one
+        /// of several types within `f`. This merely gives such projections a uniform signature.
+        $($inline)*
+        pub fn project<__U, __F>(&self, f: __F) -> $crate::__std::option::Option<&__U>
+        where
+            __U: ?Sized,
+            __F: $crate::__std::ops::Fn(&Self) -> $crate::__std::option::Option<&__U>,
+        {
+            f(self)
+        }
+        impl_downcast! { @impl_opts $trait_ $params [$($inline)*] { extras [$($names)*] } $($opts)* }
+    };
+    (@impl_opts [$($trait_:tt)*] [$($types:tt)*] [$($inline:tt)*] { extras [tag $($names:tt)*] } $($opts:tt)*) => {
+        /// Tags the boxed trait object with the `TypeId` of its concrete type, e.g. for sending it
+        /// through a channel to a receiver dispatching on the type.
+        $($inline)*
+        pub fn tag(self: $crate::__alloc::boxed::Box<Self>) -> $crate::TaggedMessage<Self> {
+            $crate::TaggedMessage::new(self)
+        }
+        impl_downcast! { @impl_opts [$($trait_)*] [$($types)*] [$($inline)*] { extras [$($names)*] } $($opts)* }
+    };
+    (@impl_opts [$($trait_:tt)*] [$($types:tt)*] [$($inline:tt)*] { extras [descendants_of_type $($names:tt)*] } $($opts:tt)*) => {
+        /// Returns an iterator over the descendants of the trait object of type `__T`, in depth-first
+        /// pre-order, where `children` returns the children of a node, e.g. in a scene graph. The
+        /// tree is walked lazily; see `DescendantsOfType`.
+        $($inline)*
+        pub fn descendants_of_type<'__a, __T, __F>(
+            &'__a self,
+            children: __F,
+        ) -> $crate::DescendantsOfType<'__a, Self, __T, __F>
+        where
+            __T: $($trait_)*<$($types)*>,
+            __F: $crate::__std::ops::Fn(&'__a Self) -> &'__a [$crate::__alloc::boxed::Box<Self>],
+        {
+            $crate::DescendantsOfType::new(self, children)
+        }
+        impl_downcast! { @impl_opts [$($trait_)*] [$($types)*] [$($inline)*] { extras [$($names)*] } $($opts)* }
+    };
+    (@impl_opts [$($trait_:tt)*] [$($types:tt)*] [$($inline:tt)*] { extras [has_capability $($names:tt)*] } $($opts:tt)*) => {
+        /// Returns true if `cap` was registered with `register_capability` for the object's
+        /// concrete type.
+        $($inline)*
+        pub fn has_capability<__C: $crate::__std::any::Any + $crate::__std::cmp::PartialEq>(&self, cap: __C) -> bool {
+            __requires_once_lock!(has_capability);
+            $crate::__has_capability($crate::__std::any::Any::type_id($crate::Downcast::as_any(self)), &cap)
+        }
+        impl_downcast! { @impl_opts [$($trait_)*] [$($types)*] [$($inline)*] { extras [$($names)*] } $($opts)* }
+    };
+    (@impl_opts [$($trait_:tt)*] [$($types:tt)*] [$($inline:tt)*] { extras [concrete_type_code $($names:tt)*] } $($opts:tt)*) => {
+        /// Returns the code registered with `register_type_code` for the object's concrete type,
+        /// or `None` if there is none.
+        $($inline)*
+        pub fn concrete_type_code(&self) -> $crate::__std::option::Option<u32> {
+            __requires_once_lock!(concrete_type_code);
+            $crate::__type_code($crate::__std::any::Any::type_id($crate::Downcast::as_any(self)))
+        }
+        impl_downcast! { @impl_opts [$($trait_)*] [$($types)*] [$($inline)*] { extras [$($names)*] } $($opts)* }
+    };
+    (@impl_opts [$($trait_:tt)*] [$($types:tt)*] [$($inline:tt)*] { extras [downcast_ref_addr $($names:tt)*] } $($opts:tt)*) => {
+        /// Returns a reference to the object within the trait object along with its address if it
+        /// is of type `__T`, or `None` if it isn't. The address identifies the object, e.g. in
+        /// debugging logs, and is that of the trait object's data.
+        $($inline)*
+        pub fn downcast_ref_addr<__T: $($trait_)*<$($types)*>>(&self) -> $crate::__std::option::Option<(&__T, usize)> {
+            self.downcast_ref::<__T>().map(|obj| (obj, obj as *const __T as usize))
+        }
+        impl_downcast! { @impl_opts [$($trait_)*] [$($types)*] [$($inline)*] { extras [$($names)*] } $($opts)* }
+    };
+    (@impl_opts [$($trait_:tt)*] [$($types:tt)*] [$($inline:tt)*] { extras [downcast_ref_reupcast $($names:tt)*] } $($opts:tt)*) => {
+        /// Returns a reference to the object within the trait object if it is of type `__T`,
+        /// together with a function converting such a reference back into a trait object
+        /// reference. Returns `None` if it isn't of type `__T`.
+        $($inline)*
+        pub fn downcast_ref_reupcast<__T: $($trait_)*<$($types)*>>(
+            &self
+        ) -> $crate::__std::option::Option<(&__T, fn(&__T) -> &Self)> {
+            let upcast: fn(&__T) -> &Self = |obj| obj;
+            self.downcast_ref::<__T>().map(|obj| (obj, upcast))
+        }
+        impl_downcast! { @impl_opts [$($trait_)*] [$($types)*] [$($inline)*] { extras [$($names)*] } $($opts)* }
+    };
+    (@impl_opts [$($trait_:tt)*] [$($types:tt)*] [$($inline:tt)*] { extras [downcast_owned_or_keep $($names:tt)*] } $($opts:tt)*) => {
+        /// Returns `DowncastOutcome::Concrete` with the boxed object if the underlying object is
+        /// of type `__T`, or `DowncastOutcome::Original` with the original boxed trait if it isn't.
+        $($inline)*
+        pub fn downcast_owned_or_keep<__T: $($trait_)*<$($types)*>>(
+            self: $crate::__alloc::boxed::Box<Self>
+        ) -> $crate::DowncastOutcome<__T, Self> {
+            match self.downcast::<__T>() {
+                Ok(obj) => $crate::DowncastOutcome::Concrete(obj),
+                Err(this) => $crate::DowncastOutcome::Original(this),
+            }
+        }
+        impl_downcast! { @impl_opts [$($trait_)*] [$($types)*] [$($inline)*] { extras [$($names)*] } $($opts)* }
+    };
+    (@impl_opts [$($trait_:tt)*] [$($types:tt)*] [$($inline:tt)*] { extras [assert_concrete $($names:tt)*] } $($opts:tt)*) => {
+        /// Asserts that the trait object wraps an object of type `__T` in debug builds; a cheap
+        /// invariant check that compiles out in release builds.
+        ///
+        /// # Panics
+        ///
+        /// Panics in debug builds if the object isn't of type `__T`.
+        $($inline)*
+        pub fn assert_concrete<__T: $($trait_)*<$($types)*>>(&self) {
+            $crate::__std::debug_assert!(
+                self.is::<__T>(),
+                "expected `{}`, found `{}`",
+                $crate::__std::any::type_name::<__T>(),
+                $crate::Downcast::__type_name(self));
+        }
+        impl_downcast! { @impl_opts [$($trait_)*] [$($types)*] [$($inline)*] { extras [$($names)*] } $($opts)* }
+    };
+    (@impl_opts [$($trait_:tt)*] [$($types:tt)*] [$($inline:tt)*] { extras [downcast_ref_named $($names:tt)*] } $($opts:tt)*) => {
+        /// Returns a reference to the object within the trait object if it is of type `__T`, or
+        /// the names of `__T` and of the object's type, in that order, if it isn't. Unlike
+        /// `try_downcast_ref`, the error is just a pair of `&'static str`s.
+        $($inline)*
+        pub fn downcast_ref_named<__T: $($trait_)*<$($types)*>>(
+            &self
+        ) -> $crate::__std::result::Result<&__T, (&'static str, &'static str)> {
+            self.downcast_ref::<__T>().ok_or_else(|| {
+                ($crate::__std::any::type_name::<__T>(), $crate::Downcast::__type_name(self))
+            })
+        }
+        impl_downcast! { @impl_opts [$($trait_)*] [$($types)*] [$($inline)*] { extras [$($names)*] } $($opts)* }
+    };
+    (@impl_opts $trait_:tt $params:tt $inline:tt { extras [$name:tt $($names:tt)*] } $($opts:tt)*) => {
+        $crate::__std::compile_error!($crate::__std::concat!(
+            "unknown method `", $crate::__std::stringify!($name), "` in `extras => [...]`"
+        ));
+    };
+    (@impl_opts $trait_:tt $params:tt [$($inline:tt)*] { single $ty:ty } $($opts:tt)*) => {
+        /// Converts the boxed trait object into the trait's only implementor, which cannot fail
+        /// since the trait extends `SingleImpl`.
+        $($inline)*
+        pub fn into_inner(self: $crate::__alloc::boxed::Box<Self>) -> $crate::__alloc::boxed::Box<$ty> {
+            $crate::__assert_single_impl::<$ty, Self>();
+            match self.downcast::<$ty>() {
+                $crate::__std::result::Result::Ok(obj) => obj,
+                $crate::__std::result::Result::Err(obj) => $crate::__std::unreachable!(
+                    "`{}` implements a trait sealed for `{}`",
+                    $crate::Downcast::__type_name(&*obj),
+                    $crate::__std::any::type_name::<$ty>()),
+            }
+        }
+        impl_downcast! { @impl_opts $trait_ $params [$($inline)*] $($opts)* }
+    };
+
+    (@impl_opts $trait_:tt $params:tt [$($inline:tt)*] { selector $name:ident [$($ty:ident),* $(,)*] } $($opts:tt)*) => {
+        /// Returns the object as `&dyn Any` if its concrete type is the one selected by `sel`, or
+        /// `None` if it isn't.
+        $($inline)*
+        pub fn downcast_selected(&self, sel: $name) -> $crate::__std::option::Option<&dyn $crate::__std::any::Any> {
+            let matches = match sel {
+                $( $name::$ty => self.is::<$ty>(), )*
+            };
+            if matches {
+                $crate::__std::option::Option::Some($crate::Downcast::as_any(self))
+            } else {
+                $crate::__std::option::Option::None
+            }
+        }
+        impl_downcast! { @impl_opts $trait_ $params [$($inline)*] $($opts)* }
+    };
+
+    (@impl_opts $trait_:tt $params:tt [$($inline:tt)*] { order [$($ty:ty),* $(,)*] } $($opts:tt)*) => {
+        /// Returns the object as `&dyn Any` if its concrete type is the `n`th (counting from 0) of
+        /// the types listed in `order => [...]`, or `None` if it isn't or `n` is out of range.
+        $($inline)*
+        pub fn nth_type_ref(&self, n: usize) -> $crate::__std::option::Option<&dyn $crate::__std::any::Any> {
+            let is_nth: &[fn(&Self) -> bool] = &[$(|obj: &Self| obj.is::<$ty>()),*];
+            match is_nth.get(n) {
+                $crate::__std::option::Option::Some(is_nth) if is_nth(self) =>
+                    $crate::__std::option::Option::Some($crate::Downcast::as_any(self)),
+                _ => $crate::__std::option::Option::None,
+            }
+        }
+        impl_downcast! { @impl_opts $trait_ $params [$($inline)*] $($opts)* }
+    };
+
+    (@impl_opts $trait_:tt $params:tt [$($inline:tt)*] { header [$hdr:ty] [$(($ty:ty, $proj:expr)),* $(,)*] } $($opts:tt)*) => {
+        /// Returns the header shared by the types listed in `header => [...]`, projected out of
+        /// the object by the closure given for its concrete type, or `None` if it is of another
+        /// type.
+        $($inline)*
+        pub fn common_header(&self) -> $crate::__std::option::Option<&$hdr> {
+            $(
+                if let $crate::__std::option::Option::Some(obj) = self.downcast_ref::<$ty>() {
+                    let project: fn(&$ty) -> &$hdr = $proj;
+                    return $crate::__std::option::Option::Some(project(obj));
+                }
+            )*
+            $crate::__std::option::Option::None
+        }
+        impl_downcast! { @impl_opts $trait_ $params [$($inline)*] $($opts)* }
+    };
+
+    (@impl_opts $trait_:tt $params:tt [$($inline:tt)*] { kind $name:ident $attrs:tt [$($ty:ident),* $(,)*] } $($opts:tt)*) => {
+        /// Returns the variant of the object's concrete type among those listed in
+        /// `kind => [...]`, or `Unknown` if it is of another type.
+        $($inline)*
+        pub fn kind(&self) -> $name {
+            let id = $crate::__std::any::Any::type_id($crate::Downcast::as_any(self));
+            $( if id == $crate::__type_id::<$ty>() { return $name::$ty; } )*
+            $name::Unknown
+        }
+        impl_downcast! { @impl_opts $trait_ $params [$($inline)*] $($opts)* }
+    };
+    (@impl_opts $trait_:tt $params:tt [$($inline:tt)*] { ref_enum $name:ident [$($ty:ident),* $(,)*] } $($opts:tt)*) => {
+        /// Borrows the object as the variant of its concrete type among those listed in
+        /// `ref_enum => [...]`, or as `Other` if it is of another type.
+        $($inline)*
+        pub fn downcast_union(&self) -> $name<'_, Self> {
+            $(
+                if let $crate::__std::option::Option::Some(obj) = self.downcast_ref::<$ty>() {
+                    return $name::$ty(obj);
+                }
+            )*
+            $name::Other(self)
+        }
+        impl_downcast! { @impl_opts $trait_ $params [$($inline)*] $($opts)* }
+    };
+    (@impl_opts $trait_:tt $params:tt [$($inline:tt)*] { predicates [$($ty:ty),* $(,)*] } $($opts:tt)*) => {
+        const __TYPE_PREDICATES: &'static [fn(&Self) -> bool] = &[$(Self::is::<$ty>),*];
+        /// Returns the `is` predicates of the types listed in `predicates => [...]`, in order.
+        $($inline)*
+        pub fn type_predicates() -> &'static [fn(&Self) -> bool] {
+            Self::__TYPE_PREDICATES
+        }
+        impl_downcast! { @impl_opts $trait_ $params [$($inline)*] $($opts)* }
+    };
+
+    // Items requested through options that live outside of the trait object's impl.
+    (@items $cfg:tt) => {};
+    (@items [$($cfg:tt)*] { selector $name:ident [$($ty:ident),* $(,)*] } $($opts:tt)*) => {
+        $($cfg)*
+        /// Selects one of the concrete types listed in `selector => [...]` for `downcast_selected`.
+        #[allow(missing_docs)]
+        #[derive(
+            $crate::__std::clone::Clone, $crate::__std::marker::Copy, $crate::__std::fmt::Debug,
+            $crate::__std::cmp::PartialEq, $crate::__std::cmp::Eq, $crate::__std::hash::Hash,
+        )]
+        pub enum $name { $($ty),* }
+        impl_downcast! { @items [$($cfg)*] $($opts)* }
+    };
+    (@items [$($cfg:tt)*] { kind $name:ident [$($hidden:ident)*] [$($ty:ident),* $(,)*] } $($opts:tt)*) => {
+        $($cfg)*
+        /// The concrete types listed in `kind => [...]`, as returned by `kind`.
+        #[allow(missing_docs, unknown_lints, clippy::manual_non_exhaustive)]
+        #[derive(
+            $crate::__std::clone::Clone, $crate::__std::marker::Copy, $crate::__std::fmt::Debug,
+            $crate::__std::cmp::PartialEq, $crate::__std::cmp::Eq, $crate::__std::hash::Hash,
+        )]
+        pub enum $name {
+            $($ty,)*
+            Unknown,
+            // Stands in for `#[non_exhaustive]`, which requires Rust 1.40.
+            $(#[doc(hidden)] $hidden,)*
+        }
+        impl_downcast! { @items [$($cfg)*] $($opts)* }
+    };
+    (@items [$($cfg:tt)*] { ref_enum $name:ident [$($ty:ident),* $(,)*] } $($opts:tt)*) => {
+        $($cfg)*
+        /// A reference to a trait object of type `B`, downcast to whichever of the types listed in
+        /// `ref_enum => [...]` it is, as returned by `downcast_union`.
+        #[allow(missing_docs)]
+        pub enum $name<'a, B: ?Sized + 'a> { $($ty(&'a $ty),)* Other(&'a B) }
+        $($cfg)*
+        impl<'a, B: ?Sized + 'a> $crate::__std::clone::Clone for $name<'a, B> {
+            fn clone(&self) -> Self { *self }
+        }
+        $($cfg)*
+        impl<'a, B: ?Sized + 'a> $crate::__std::marker::Copy for $name<'a, B> {}
+        impl_downcast! { @items [$($cfg)*] $($opts)* }
+    };
+    (@items [$($cfg:tt)*] { owned_enum $name:ident [$($ty:ident),* $(,)*] } $($opts:tt)*) => {
+        $($cfg)*
+        /// A boxed trait object of type `B`, downcast to whichever of the types listed in
+        /// `owned_enum => [...]` it is, as converted from the boxed trait object with `From`.
+        #[allow(missing_docs)]
+        pub enum $name<B: ?Sized> {
+            $($ty($crate::__alloc::boxed::Box<$ty>),)*
+            Other($crate::__alloc::boxed::Box<B>),
+        }
+        impl_downcast! { @items [$($cfg)*] $($opts)* }
+    };
+    (@items $cfg:tt $opt:tt $($opts:tt)*) => { impl_downcast! { @items $cfg $($opts)* } };
+
+    (@inject_where [$($before:tt)*] types [] where [] [$($after:tt)*]) => {
+        impl_downcast! { @as_item $($before)* $($after)* }
+    };
+
+    (@inject_where [$($before:tt)*] types [$($types:ident),*] where [] [$($after:tt)*]) => {
+        impl_downcast! {
+            @as_item
+                $($before)*
+                where $( $types: $crate::__std::any::Any + 'static ),*
+                $($after)*
+        }
+    };
+    (@inject_where [$($before:tt)*] types [$($types:ident),*] where [$($preds:tt)+] [$($after:tt)*]) => {
+        impl_downcast! {
+            @as_item
+                $($before)*
+                where
+                    $( $types: $crate::__std::any::Any + 'static, )*
+                    $($preds)*
+                $($after)*
+        }
+    };
+
+    (@as_item $i:item) => { $i };
+
+    // Parses what follows the `sync`/`concrete` prefixes. The parsed pieces are accumulated as
+    // `[generics] [params] [types]`, where `generics` are the impl's generic parameters, `params`
+    // are the trait's parameters and associated type bindings, and `types` are the generic types
+    // that must additionally be `Any + 'static`.
+    (@parse $kind:tt $mode:tt dyn $($rest:tt)+) => { impl_downcast! { @parse $kind $mode $($rest)+ } };
+    (@parse $kind:tt $mode:tt $($rest:tt)+) => { impl_downcast! { @parse_path $kind $mode [] $($rest)+ } };
+
+    // The trait path, which may be relative, e.g. to `crate`, `self` or `super`, or global. Trait
+    // objects are spelled `dyn (Path<..>)`, allowing `unused_parens`, since edition 2015 parses
+    // `dyn ::a::B` as a path.
+    (@parse_path $kind:tt $mode:tt [] :: $($rest:tt)+) => {
+        impl_downcast! { @parse_path $kind $mode [::] $($rest)+ }
+    };
+    (@parse_path $kind:tt $mode:tt [$($path:tt)*] $seg:ident :: $($rest:tt)+) => {
+        impl_downcast! { @parse_path $kind $mode [$($path)* $seg ::] $($rest)+ }
+    };
+    (@parse_path $kind:tt $mode:tt [$($path:tt)*] $trait_:ident < $($rest:tt)*) => {
+        impl_downcast! { @parse_params $kind $mode [$($path)* $trait_] [] [] [] [] $($rest)* }
+    };
+    (@parse_path $kind:tt $mode:tt [$($path:tt)*] $trait_:ident $($rest:tt)*) => {
+        impl_downcast! { @parse_assoc $kind [$($path)* $trait_] [] [] [] [] $($rest)* }
+    };
+
+    // Type parameters. Lifetimes appear in the trait object's type, so `Any` requires them to be
+    // `'static`.
+    (@parse_params $kind:tt $mode:tt $trait_:tt [$($gen:tt)*] [$($params:tt)*] $types:tt $bounds:tt
+        > $($rest:tt)*
+    ) => {
+        impl_downcast! { @parse_assoc $kind $trait_ [$($gen)*] [$($params)*] $types $bounds $($rest)* }
+    };
+    (@parse_params $kind:tt $mode:tt $trait_:tt $gen:tt [$($params:tt)*] $types:tt $bounds:tt
+        'static , $($rest:tt)*
+    ) => {
+        impl_downcast! { @parse_params $kind $mode $trait_ $gen [$($params)* 'static,] $types $bounds $($rest)* }
+    };
+    (@parse_params $kind:tt $mode:tt $trait_:tt $gen:tt [$($params:tt)*] $types:tt $bounds:tt
+        'static > $($rest:tt)*
+    ) => {
+        impl_downcast! { @parse_assoc $kind $trait_ $gen [$($params)* 'static,] $types $bounds $($rest)* }
+    };
+    (@parse_params $kind:tt [] $trait_:tt [$($gen:tt)*] [$($params:tt)*] $types:tt $bounds:tt
+        $lt:lifetime , $($rest:tt)*
+    ) => {
+        impl_downcast! {
+            @parse_params $kind [] $trait_ [$($gen)* $lt: 'static,] [$($params)* $lt,] $types $bounds $($rest)*
+        }
+    };
+    (@parse_params $kind:tt [] $trait_:tt [$($gen:tt)*] [$($params:tt)*] $types:tt $bounds:tt
+        $lt:lifetime > $($rest:tt)*
+    ) => {
+        impl_downcast! { @parse_assoc $kind $trait_ [$($gen)* $lt: 'static,] [$($params)* $lt,] $types $bounds $($rest)* }
+    };
+    // Const parameters (requires Rust 1.51), which are not types and so aren't bounded by `Any`.
+    (@parse_params $kind:tt [] $trait_:tt [$($gen:tt)*] [$($params:tt)*] $types:tt $bounds:tt
+        const $name:ident : $cty:ty , $($rest:tt)*
+    ) => {
+        impl_downcast! {
+            @parse_params $kind [] $trait_ [$($gen)* const $name: $cty,] [$($params)* $name,] $types $bounds $($rest)*
+        }
+    };
+    (@parse_params $kind:tt [] $trait_:tt [$($gen:tt)*] [$($params:tt)*] $types:tt $bounds:tt
+        const $name:ident : $cty:ty > $($rest:tt)*
+    ) => {
+        impl_downcast! {
+            @parse_assoc $kind $trait_ [$($gen)* const $name: $cty,] [$($params)* $name,] $types $bounds $($rest)*
+        }
+    };
+    (@parse_params $kind:tt [] $trait_:tt [$($gen:tt)*] [$($params:tt)*] [$($types:tt)*] $bounds:tt
+        $ty:ident , $($rest:tt)*
+    ) => {
+        impl_downcast! {
+            @parse_params $kind [] $trait_ [$($gen)* $ty,] [$($params)* $ty,] [$($types)* $ty] $bounds $($rest)*
+        }
+    };
+    (@parse_params $kind:tt [] $trait_:tt [$($gen:tt)*] [$($params:tt)*] [$($types:tt)*] $bounds:tt
+        $ty:ident > $($rest:tt)*
+    ) => {
+        impl_downcast! {
+            @parse_assoc $kind $trait_ [$($gen)* $ty,] [$($params)* $ty,] [$($types)* $ty] $bounds $($rest)*
+        }
+    };
+    // Type parameters with inline bounds, e.g. `T: Copy + Into<u64>`, which are moved into the
+    // where clause. The bound extends up to the first `,` or `>` outside its own angle brackets,
+    // whose nesting is counted in `[@ ..]`.
+    (@parse_params $kind:tt [] $trait_:tt $gen:tt $params:tt $types:tt $bounds:tt
+        $ty:ident : $($rest:tt)*
+    ) => {
+        impl_downcast! { @parse_bound $kind $trait_ $gen $params $types $bounds $ty [] [] $($rest)* }
+    };
+    (@parse_bound $kind:tt $trait_:tt [$($gen:tt)*] [$($params:tt)*] [$($types:tt)*] [$($bounds:tt)*]
+        $ty:ident [$($bound:tt)*] [] , $($rest:tt)*
+    ) => {
+        impl_downcast! {
+            @parse_params $kind [] $trait_
+                [$($gen)* $ty,] [$($params)* $ty,] [$($types)* $ty] [$($bounds)* $ty: $($bound)*,] $($rest)*
+        }
+    };
+    (@parse_bound $kind:tt $trait_:tt [$($gen:tt)*] [$($params:tt)*] [$($types:tt)*] [$($bounds:tt)*]
+        $ty:ident [$($bound:tt)*] [] > $($rest:tt)*
+    ) => {
+        impl_downcast! {
+            @parse_assoc $kind $trait_
+                [$($gen)* $ty,] [$($params)* $ty,] [$($types)* $ty] [$($bounds)* $ty: $($bound)*,] $($rest)*
+        }
+    };
+    (@parse_bound $kind:tt $trait_:tt $gen:tt $params:tt $types:tt $bounds:tt
+        $ty:ident [$($bound:tt)*] [$($depth:tt)*] < $($rest:tt)*
+    ) => {
+        impl_downcast! { @parse_bound $kind $trait_ $gen $params $types $bounds $ty [$($bound)* <] [@ $($depth)*] $($rest)* }
+    };
+    (@parse_bound $kind:tt $trait_:tt $gen:tt $params:tt $types:tt $bounds:tt
+        $ty:ident [$($bound:tt)*] [@ $($depth:tt)*] > $($rest:tt)*
+    ) => {
+        impl_downcast! { @parse_bound $kind $trait_ $gen $params $types $bounds $ty [$($bound)* >] [$($depth)*] $($rest)* }
+    };
+    // `>>` is a single token, e.g. in `T: Into<Vec<u8>>` or at the end of `Base<T: Into<u8>>`.
+    (@parse_bound $kind:tt $trait_:tt $gen:tt $params:tt $types:tt $bounds:tt
+        $ty:ident [$($bound:tt)*] [@ @ $($depth:tt)*] >> $($rest:tt)*
+    ) => {
+        impl_downcast! { @parse_bound $kind $trait_ $gen $params $types $bounds $ty [$($bound)* > >] [$($depth)*] $($rest)* }
+    };
+    (@parse_bound $kind:tt $trait_:tt $gen:tt $params:tt $types:tt $bounds:tt
+        $ty:ident [$($bound:tt)*] [@] >> $($rest:tt)*
+    ) => {
+        impl_downcast! { @parse_bound $kind $trait_ $gen $params $types $bounds $ty [$($bound)* >] [] > $($rest)* }
+    };
+    (@parse_bound $kind:tt $trait_:tt $gen:tt $params:tt $types:tt $bounds:tt
+        $ty:ident [$($bound:tt)*] $depth:tt $tok:tt $($rest:tt)*
+    ) => {
+        impl_downcast! { @parse_bound $kind $trait_ $gen $params $types $bounds $ty [$($bound)* $tok] $depth $($rest)* }
+    };
+    // Concretely-parametrized types, including const arguments given as literals or blocks.
+    (@parse_params $kind:tt [concrete] $trait_:tt $gen:tt [$($params:tt)*] $types:tt $bounds:tt
+        $value:literal , $($rest:tt)*
+    ) => {
+        impl_downcast! { @parse_params $kind [concrete] $trait_ $gen [$($params)* $value,] $types $bounds $($rest)* }
+    };
+    (@parse_params $kind:tt [concrete] $trait_:tt $gen:tt [$($params:tt)*] $types:tt $bounds:tt
+        $value:literal > $($rest:tt)*
+    ) => {
+        impl_downcast! { @parse_assoc $kind $trait_ $gen [$($params)* $value,] $types $bounds $($rest)* }
+    };
+    (@parse_params $kind:tt [concrete] $trait_:tt $gen:tt [$($params:tt)*] $types:tt $bounds:tt
+        $value:block , $($rest:tt)*
+    ) => {
+        impl_downcast! { @parse_params $kind [concrete] $trait_ $gen [$($params)* $value,] $types $bounds $($rest)* }
+    };
+    (@parse_params $kind:tt [concrete] $trait_:tt $gen:tt [$($params:tt)*] $types:tt $bounds:tt
+        $value:block > $($rest:tt)*
+    ) => {
+        impl_downcast! { @parse_assoc $kind $trait_ $gen [$($params)* $value,] $types $bounds $($rest)* }
+    };
+    (@parse_params $kind:tt [concrete] $trait_:tt $gen:tt [$($params:tt)*] $types:tt $bounds:tt
+        $ty:ty , $($rest:tt)*
+    ) => {
+        impl_downcast! { @parse_params $kind [concrete] $trait_ $gen [$($params)* $ty,] $types $bounds $($rest)* }
+    };
+    (@parse_params $kind:tt [concrete] $trait_:tt $gen:tt [$($params:tt)*] $types:tt $bounds:tt
+        $ty:ty > $($rest:tt)*
+    ) => {
+        impl_downcast! { @parse_assoc $kind $trait_ $gen [$($params)* $ty,] $types $bounds $($rest)* }
+    };
+
+    // Associated types, either generic or concrete.
+    (@parse_assoc $kind:tt $trait_:tt $gen:tt $params:tt $types:tt $bounds:tt assoc $($rest:tt)+) => {
+        impl_downcast! { @parse_assoc_list $kind $trait_ $gen $params $types $bounds $($rest)+ }
+    };
+    (@parse_assoc $kind:tt $trait_:tt $gen:tt $params:tt $types:tt $bounds:tt $($rest:tt)*) => {
+        impl_downcast! { @parse_where $kind $trait_ $gen $params $types $bounds $($rest)* }
+    };
+    // An `impl Trait` cannot be named in `dyn Trait<H = ...>`; point the user at a type alias.
+    (@parse_assoc_list $kind:tt $trait_:tt $gen:tt $params:tt $types:tt $bounds:tt
+        $atype:ident = impl $($rest:tt)*
+    ) => {
+        $crate::__std::compile_error!(
+            "`impl Trait` cannot be used as a concrete associated type in `impl_downcast!`; \
+             declare `type Alias = impl Trait;` (nightly `type_alias_impl_trait`) and use \
+             `assoc H = Alias`, or leave the associated type generic with `assoc H`"
+        );
+    };
+    // What follows a trailing comma.
+    (@parse_assoc_list $kind:tt $trait_:tt $gen:tt $params:tt $types:tt $bounds:tt) => {
+        impl_downcast! { @parse_where $kind $trait_ $gen $params $types $bounds }
+    };
+    (@parse_assoc_list $kind:tt $trait_:tt $gen:tt $params:tt $types:tt $bounds:tt
+        where $($rest:tt)+
+    ) => {
+        impl_downcast! { @parse_where $kind $trait_ $gen $params $types $bounds where $($rest)+ }
+    };
+    (@parse_assoc_list $kind:tt $trait_:tt $gen:tt $params:tt $types:tt $bounds:tt
+        ; $($rest:tt)*
+    ) => {
+        impl_downcast! { @parse_where $kind $trait_ $gen $params $types $bounds ; $($rest)* }
+    };
+    (@parse_assoc_list $kind:tt $trait_:tt $gen:tt $params:tt $types:tt $bounds:tt
+        $opt:ident => $($rest:tt)*
+    ) => {
+        impl_downcast! { @parse_where $kind $trait_ $gen $params $types $bounds $opt => $($rest)* }
+    };
+    (@parse_assoc_list $kind:tt $trait_:tt $gen:tt [$($params:tt)*] $types:tt $bounds:tt
+        $atype:ident = $aty:ty , $($rest:tt)*
+    ) => {
+        impl_downcast! { @parse_assoc_list $kind $trait_ $gen [$($params)* $atype = $aty,] $types $bounds $($rest)* }
+    };
+    (@parse_assoc_list $kind:tt $trait_:tt $gen:tt [$($params:tt)*] $types:tt $bounds:tt
+        $atype:ident = $aty:ty
+    ) => {
+        impl_downcast! { @parse_where $kind $trait_ $gen [$($params)* $atype = $aty,] $types $bounds }
+    };
+    (@parse_assoc_list $kind:tt $trait_:tt $gen:tt [$($params:tt)*] $types:tt $bounds:tt
+        $atype:ident = $aty:ty ; $($rest:tt)*
+    ) => {
+        impl_downcast! { @parse_where $kind $trait_ $gen [$($params)* $atype = $aty,] $types $bounds ; $($rest)* }
+    };
+    (@parse_assoc_list $kind:tt $trait_:tt $gen:tt [$($params:tt)*] $types:tt $bounds:tt
+        $atype:ident = $aty:ty where $($rest:tt)+
+    ) => {
+        impl_downcast! { @parse_where $kind $trait_ $gen [$($params)* $atype = $aty,] $types $bounds where $($rest)+ }
+    };
+    // Generic associated types with inline bounds, e.g. `H: Clone + Into<u64>`, which are moved
+    // into the where clause. The bound extends up to the first `,` outside its own angle
+    // brackets, whose nesting is counted in `[@ ..]`, or up to a `where` clause or an option.
+    (@parse_assoc_list $kind:tt $trait_:tt [$($gen:tt)*] [$($params:tt)*] [$($types:tt)*] [$($bounds:tt)*]
+        $atype:ident : $($rest:tt)*
+    ) => {
+        impl_downcast! {
+            @parse_assoc_bound $kind $trait_
+                [$($gen)* $atype,] [$($params)* $atype = $atype,] [$($types)* $atype] [$($bounds)* $atype:] []
+                $($rest)*
+        }
+    };
+    (@parse_assoc_bound $kind:tt $trait_:tt $gen:tt $params:tt $types:tt [$($bounds:tt)*] []
+        , $($rest:tt)*
+    ) => {
+        impl_downcast! { @parse_assoc_list $kind $trait_ $gen $params $types [$($bounds)*,] $($rest)* }
+    };
+    (@parse_assoc_bound $kind:tt $trait_:tt $gen:tt $params:tt $types:tt [$($bounds:tt)*] []) => {
+        impl_downcast! { @parse_where $kind $trait_ $gen $params $types [$($bounds)*,] }
+    };
+    (@parse_assoc_bound $kind:tt $trait_:tt $gen:tt $params:tt $types:tt [$($bounds:tt)*] []
+        where $($rest:tt)+
+    ) => {
+        impl_downcast! { @parse_where $kind $trait_ $gen $params $types [$($bounds)*,] where $($rest)+ }
+    };
+    (@parse_assoc_bound $kind:tt $trait_:tt $gen:tt $params:tt $types:tt [$($bounds:tt)*] []
+        ; $($rest:tt)*
+    ) => {
+        impl_downcast! { @parse_where $kind $trait_ $gen $params $types [$($bounds)*,] ; $($rest)* }
+    };
+    (@parse_assoc_bound $kind:tt $trait_:tt $gen:tt $params:tt $types:tt [$($bounds:tt)*] []
+        $opt:ident => $($rest:tt)*
+    ) => {
+        impl_downcast! { @parse_where $kind $trait_ $gen $params $types [$($bounds)*,] $opt => $($rest)* }
+    };
+    (@parse_assoc_bound $kind:tt $trait_:tt $gen:tt $params:tt $types:tt [$($bounds:tt)*] []
+        feature = $($rest:tt)*
+    ) => {
+        impl_downcast! { @parse_where $kind $trait_ $gen $params $types [$($bounds)*,] feature = $($rest)* }
+    };
+    (@parse_assoc_bound $kind:tt $trait_:tt $gen:tt $params:tt $types:tt [$($bounds:tt)*] []
+        doc_hidden $($rest:tt)*
+    ) => {
+        impl_downcast! { @parse_where $kind $trait_ $gen $params $types [$($bounds)*,] doc_hidden $($rest)* }
+    };
+    (@parse_assoc_bound $kind:tt $trait_:tt $gen:tt $params:tt $types:tt [$($bounds:tt)*] []
+        unchecked $($rest:tt)*
+    ) => {
+        impl_downcast! { @parse_where $kind $trait_ $gen $params $types [$($bounds)*,] unchecked $($rest)* }
+    };
+    (@parse_assoc_bound $kind:tt $trait_:tt $gen:tt $params:tt $types:tt [$($bounds:tt)*] []
+        no_inline $($rest:tt)*
+    ) => {
+        impl_downcast! { @parse_where $kind $trait_ $gen $params $types [$($bounds)*,] no_inline $($rest)* }
+    };
+    (@parse_assoc_bound $kind:tt $trait_:tt $gen:tt $params:tt $types:tt [$($bounds:tt)*] []
+        selector $($rest:tt)*
+    ) => {
+        impl_downcast! { @parse_where $kind $trait_ $gen $params $types [$($bounds)*,] selector $($rest)* }
+    };
+    (@parse_assoc_bound $kind:tt $trait_:tt $gen:tt $params:tt $types:tt [$($bounds:tt)*] []
+        kind $($rest:tt)*
+    ) => {
+        impl_downcast! { @parse_where $kind $trait_ $gen $params $types [$($bounds)*,] kind $($rest)* }
+    };
+    (@parse_assoc_bound $kind:tt $trait_:tt $gen:tt $params:tt $types:tt [$($bounds:tt)*] []
+        ref_enum $($rest:tt)*
+    ) => {
+        impl_downcast! { @parse_where $kind $trait_ $gen $params $types [$($bounds)*,] ref_enum $($rest)* }
+    };
+    (@parse_assoc_bound $kind:tt $trait_:tt $gen:tt $params:tt $types:tt [$($bounds:tt)*] []
+        owned_enum $($rest:tt)*
+    ) => {
+        impl_downcast! { @parse_where $kind $trait_ $gen $params $types [$($bounds)*,] owned_enum $($rest)* }
+    };
+    (@parse_assoc_bound $kind:tt $trait_:tt $gen:tt $params:tt $types:tt [$($bounds:tt)*] []
+        header $($rest:tt)*
+    ) => {
+        impl_downcast! { @parse_where $kind $trait_ $gen $params $types [$($bounds)*,] header $($rest)* }
+    };
+    (@parse_assoc_bound $kind:tt $trait_:tt $gen:tt $params:tt $types:tt [$($bounds:tt)*] [$($depth:tt)*]
+        < $($rest:tt)*
+    ) => {
+        impl_downcast! { @parse_assoc_bound $kind $trait_ $gen $params $types [$($bounds)* <] [@ $($depth)*] $($rest)* }
+    };
+    (@parse_assoc_bound $kind:tt $trait_:tt $gen:tt $params:tt $types:tt [$($bounds:tt)*] [@ $($depth:tt)*]
+        > $($rest:tt)*
+    ) => {
+        impl_downcast! { @parse_assoc_bound $kind $trait_ $gen $params $types [$($bounds)* >] [$($depth)*] $($rest)* }
+    };
+    (@parse_assoc_bound $kind:tt $trait_:tt $gen:tt $params:tt $types:tt [$($bounds:tt)*] [@ @ $($depth:tt)*]
+        >> $($rest:tt)*
+    ) => {
+        impl_downcast! { @parse_assoc_bound $kind $trait_ $gen $params $types [$($bounds)* > >] [$($depth)*] $($rest)* }
+    };
+    (@parse_assoc_bound $kind:tt $trait_:tt $gen:tt $params:tt $types:tt [$($bounds:tt)*] $depth:tt
+        $tok:tt $($rest:tt)*
+    ) => {
+        impl_downcast! { @parse_assoc_bound $kind $trait_ $gen $params $types [$($bounds)* $tok] $depth $($rest)* }
+    };
+    (@parse_assoc_list $kind:tt $trait_:tt [$($gen:tt)*] [$($params:tt)*] [$($types:tt)*] $bounds:tt
+        $atype:ident , $($rest:tt)*
+    ) => {
+        impl_downcast! {
+            @parse_assoc_list $kind $trait_
+                [$($gen)* $atype,] [$($params)* $atype = $atype,] [$($types)* $atype] $bounds $($rest)*
+        }
+    };
+    (@parse_assoc_list $kind:tt $trait_:tt [$($gen:tt)*] [$($params:tt)*] [$($types:tt)*] $bounds:tt
+        $atype:ident $($rest:tt)*
+    ) => {
+        impl_downcast! {
+            @parse_where $kind $trait_
+                [$($gen)* $atype,] [$($params)* $atype = $atype,] [$($types)* $atype] $bounds $($rest)*
+        }
+    };
+
+    // Where clauses, which extend up to the first option. Bounds given inline on the parameters
+    // start off the predicates.
+    (@parse_where $kind:tt $trait_:tt $gen:tt $params:tt $types:tt $bounds:tt where $($rest:tt)+) => {
+        impl_downcast! { @parse_preds $kind $trait_ $gen $params $types $bounds $($rest)+ }
+    };
+    (@parse_where $kind:tt $trait_:tt $gen:tt $params:tt $types:tt $bounds:tt $($rest:tt)*) => {
+        impl_downcast! { @parse_opts $kind $trait_ $gen $params $types $bounds [] $($rest)* }
+    };
+    (@parse_preds $kind:tt $trait_:tt $gen:tt $params:tt $types:tt $preds:tt) => {
+        impl_downcast! { @parse_opts $kind $trait_ $gen $params $types $preds [] }
+    };
+    (@parse_preds $kind:tt $trait_:tt $gen:tt $params:tt $types:tt $preds:tt
+        ; $($rest:tt)*
+    ) => {
+        impl_downcast! { @parse_opts $kind $trait_ $gen $params $types $preds [] ; $($rest)* }
+    };
+    (@parse_preds $kind:tt $trait_:tt $gen:tt $params:tt $types:tt $preds:tt
+        $opt:ident => $($rest:tt)*
+    ) => {
+        impl_downcast! { @parse_opts $kind $trait_ $gen $params $types $preds [] $opt => $($rest)* }
+    };
+    (@parse_preds $kind:tt $trait_:tt $gen:tt $params:tt $types:tt $preds:tt
+        feature = $($rest:tt)*
+    ) => {
+        impl_downcast! { @parse_opts $kind $trait_ $gen $params $types $preds [] feature = $($rest)* }
+    };
+    (@parse_preds $kind:tt $trait_:tt $gen:tt $params:tt $types:tt $preds:tt
+        doc_hidden $($rest:tt)*
+    ) => {
+        impl_downcast! { @parse_opts $kind $trait_ $gen $params $types $preds [] doc_hidden $($rest)* }
+    };
+    (@parse_preds $kind:tt $trait_:tt $gen:tt $params:tt $types:tt $preds:tt
+        unchecked $($rest:tt)*
+    ) => {
+        impl_downcast! { @parse_opts $kind $trait_ $gen $params $types $preds [] unchecked $($rest)* }
+    };
+    (@parse_preds $kind:tt $trait_:tt $gen:tt $params:tt $types:tt $preds:tt
+        no_inline $($rest:tt)*
+    ) => {
+        impl_downcast! { @parse_opts $kind $trait_ $gen $params $types $preds [] no_inline $($rest)* }
+    };
+    (@parse_preds $kind:tt $trait_:tt $gen:tt $params:tt $types:tt $preds:tt
+        selector $name:ident => $($rest:tt)*
+    ) => {
+        impl_downcast! { @parse_opts $kind $trait_ $gen $params $types $preds [] selector $name => $($rest)* }
+    };
+    (@parse_preds $kind:tt $trait_:tt $gen:tt $params:tt $types:tt $preds:tt
+        kind $name:ident => $($rest:tt)*
+    ) => {
+        impl_downcast! { @parse_opts $kind $trait_ $gen $params $types $preds [] kind $name => $($rest)* }
+    };
+    (@parse_preds $kind:tt $trait_:tt $gen:tt $params:tt $types:tt $preds:tt
+        kind non_exhaustive $name:ident => $($rest:tt)*
+    ) => {
+        impl_downcast! { @parse_opts $kind $trait_ $gen $params $types $preds [] kind non_exhaustive $name => $($rest)* }
+    };
+    (@parse_preds $kind:tt $trait_:tt $gen:tt $params:tt $types:tt $preds:tt
+        ref_enum $name:ident => $($rest:tt)*
+    ) => {
+        impl_downcast! { @parse_opts $kind $trait_ $gen $params $types $preds [] ref_enum $name => $($rest)* }
+    };
+    (@parse_preds $kind:tt $trait_:tt $gen:tt $params:tt $types:tt $preds:tt
+        owned_enum $name:ident => $($rest:tt)*
+    ) => {
+        impl_downcast! { @parse_opts $kind $trait_ $gen $params $types $preds [] owned_enum $name => $($rest)* }
+    };
+    (@parse_preds $kind:tt $trait_:tt $gen:tt $params:tt $types:tt $preds:tt
+        header $hdr:ty => $($rest:tt)*
+    ) => {
+        impl_downcast! { @parse_opts $kind $trait_ $gen $params $types $preds [] header $hdr => $($rest)* }
+    };
+    (@parse_preds $kind:tt $trait_:tt $gen:tt $params:tt $types:tt [$($preds:tt)*]
+        $pred:tt $($rest:tt)*
+    ) => {
+        impl_downcast! { @parse_preds $kind $trait_ $gen $params $types [$($preds)* $pred] $($rest)* }
+    };
+
+    // Options.
+    (@parse_opts $kind:tt $trait_:tt $gen:tt $params:tt $types:tt $preds:tt [$($opts:tt)*]
+        clone => [$($tys:tt)*] $($rest:tt)*
+    ) => {
+        impl_downcast! {
+            @parse_opts $kind $trait_ $gen $params $types $preds [$($opts)* { clone [$($tys)*] }] $($rest)*
+        }
+    };
+    (@parse_opts $kind:tt $trait_:tt $gen:tt $params:tt $types:tt $preds:tt [$($opts:tt)*]
+        clone => $helper:ident $($rest:tt)*
+    ) => {
+        impl_downcast! {
+            @parse_opts $kind $trait_ $gen $params $types $preds [$($opts)* { clone_trait $helper }] $($rest)*
+        }
+    };
+    (@parse_opts $kind:tt $trait_:tt $gen:tt $params:tt $types:tt $preds:tt [$($opts:tt)*]
+        extras => [$($names:tt)*] $($rest:tt)*
+    ) => {
+        impl_downcast! {
+            @parse_opts $kind $trait_ $gen $params $types $preds [$($opts)* { extras [$($names)*] }] $($rest)*
+        }
+    };
+    (@parse_opts $kind:tt $trait_:tt $gen:tt $params:tt $types:tt $preds:tt [$($opts:tt)*]
+        from_any => [$($tys:tt)*] $($rest:tt)*
+    ) => {
+        impl_downcast! {
+            @parse_opts $kind $trait_ $gen $params $types $preds [$($opts)* { from_any [$($tys)*] }] $($rest)*
+        }
+    };
+    (@parse_opts $kind:tt $trait_:tt $gen:tt $params:tt $types:tt $preds:tt [$($opts:tt)*]
+        kind => [$($tys:tt)*] $($rest:tt)*
+    ) => {
+        impl_downcast! {
+            @parse_opts $kind $trait_ $gen $params $types $preds [$($opts)*] kind TypeKind => [$($tys)*] $($rest)*
+        }
+    };
+    (@parse_opts $kind:tt $trait_:tt $gen:tt $params:tt $types:tt $preds:tt [$($opts:tt)*]
+        kind non_exhaustive => [$($tys:tt)*] $($rest:tt)*
+    ) => {
+        impl_downcast! {
+            @parse_opts $kind $trait_ $gen $params $types $preds [$($opts)*] kind non_exhaustive TypeKind => [$($tys)*] $($rest)*
+        }
+    };
+    (@parse_opts $kind:tt $trait_:tt $gen:tt $params:tt $types:tt $preds:tt [$($opts:tt)*]
+        kind non_exhaustive $name:ident => [$($tys:tt)*] $($rest:tt)*
+    ) => {
+        impl_downcast! {
+            @parse_opts $kind $trait_ $gen $params $types $preds
+                [$($opts)* { kind $name [__NonExhaustive] [$($tys)*] }] $($rest)*
+        }
+    };
+    (@parse_opts $kind:tt $trait_:tt $gen:tt $params:tt $types:tt $preds:tt [$($opts:tt)*]
+        kind $name:ident => [$($tys:tt)*] $($rest:tt)*
+    ) => {
+        impl_downcast! {
+            @parse_opts $kind $trait_ $gen $params $types $preds [$($opts)* { kind $name [] [$($tys)*] }] $($rest)*
+        }
+    };
+    (@parse_opts $kind:tt $trait_:tt $gen:tt $params:tt $types:tt $preds:tt [$($opts:tt)*]
+        ref_enum $name:ident => [$($tys:tt)*] $($rest:tt)*
+    ) => {
+        impl_downcast! {
+            @parse_opts $kind $trait_ $gen $params $types $preds [$($opts)* { ref_enum $name [$($tys)*] }] $($rest)*
+        }
+    };
+    (@parse_opts $kind:tt $trait_:tt $gen:tt $params:tt $types:tt $preds:tt [$($opts:tt)*]
+        owned_enum $name:ident => [$($tys:tt)*] $($rest:tt)*
+    ) => {
+        impl_downcast! {
+            @parse_opts $kind $trait_ $gen $params $types $preds [$($opts)* { owned_enum $name [$($tys)*] }] $($rest)*
+        }
+    };
+    (@parse_opts $kind:tt $trait_:tt $gen:tt $params:tt $types:tt $preds:tt [$($opts:tt)*]
+        try_from => [$($tys:tt)*] $($rest:tt)*
+    ) => {
+        impl_downcast! {
+            @parse_opts $kind $trait_ $gen $params $types $preds [$($opts)* { try_from [$($tys)*] }] $($rest)*
+        }
+    };
+    (@parse_opts $kind:tt $trait_:tt $gen:tt $params:tt $types:tt $preds:tt [$($opts:tt)*]
+        order => [$($tys:tt)*] $($rest:tt)*
+    ) => {
+        impl_downcast! {
+            @parse_opts $kind $trait_ $gen $params $types $preds [$($opts)* { order [$($tys)*] }] $($rest)*
+        }
+    };
+    (@parse_opts $kind:tt $trait_:tt $gen:tt $params:tt $types:tt $preds:tt [$($opts:tt)*]
+        predicates => [$($tys:tt)*] $($rest:tt)*
+    ) => {
+        impl_downcast! {
+            @parse_opts $kind $trait_ $gen $params $types $preds [$($opts)* { predicates [$($tys)*] }] $($rest)*
+        }
+    };
+    (@parse_opts $kind:tt $trait_:tt $gen:tt $params:tt $types:tt $preds:tt [$($opts:tt)*]
+        selector => [$($tys:tt)*] $($rest:tt)*
+    ) => {
+        impl_downcast! {
+            @parse_opts $kind $trait_ $gen $params $types $preds [$($opts)*] selector TargetSelector => [$($tys)*] $($rest)*
+        }
+    };
+    (@parse_opts $kind:tt $trait_:tt $gen:tt $params:tt $types:tt $preds:tt [$($opts:tt)*]
+        selector $name:ident => [$($tys:tt)*] $($rest:tt)*
+    ) => {
+        impl_downcast! {
+            @parse_opts $kind $trait_ $gen $params $types $preds [$($opts)* { selector $name [$($tys)*] }] $($rest)*
+        }
+    };
+    (@parse_opts $kind:tt $trait_:tt $gen:tt $params:tt $types:tt $preds:tt [$($opts:tt)*]
+        header $hdr:ty => [$($tys:tt)*] $($rest:tt)*
+    ) => {
+        impl_downcast! {
+            @parse_opts $kind $trait_ $gen $params $types $preds [$($opts)* { header [$hdr] [$($tys)*] }] $($rest)*
+        }
+    };
+    (@parse_opts $kind:tt $trait_:tt $gen:tt $params:tt $types:tt $preds:tt [$($opts:tt)*]
+        single => $ty:ty
+    ) => {
+        impl_downcast! { @parse_opts $kind $trait_ $gen $params $types $preds [$($opts)* { single $ty }] }
+    };
+    (@parse_opts $kind:tt $trait_:tt $gen:tt $params:tt $types:tt $preds:tt [$($opts:tt)*]
+        single => $ty:ty ; $($rest:tt)*
+    ) => {
+        impl_downcast! { @parse_opts $kind $trait_ $gen $params $types $preds [$($opts)* { single $ty }] ; $($rest)* }
+    };
+    (@parse_opts $kind:tt $trait_:tt $gen:tt $params:tt $types:tt $preds:tt [$($opts:tt)*]
+        feature = $feature:tt $($rest:tt)*
+    ) => {
+        impl_downcast! {
+            @parse_opts $kind $trait_ $gen $params $types $preds [$($opts)* { feature $feature }] $($rest)*
+        }
+    };
+    (@parse_opts $kind:tt $trait_:tt $gen:tt $params:tt $types:tt $preds:tt [$($opts:tt)*]
+        doc_hidden $($rest:tt)*
+    ) => {
+        impl_downcast! {
+            @parse_opts $kind $trait_ $gen $params $types $preds [$($opts)* { doc_hidden }] $($rest)*
+        }
+    };
+    (@parse_opts $kind:tt $trait_:tt $gen:tt $params:tt $types:tt $preds:tt [$($opts:tt)*]
+        unchecked $($rest:tt)*
+    ) => {
+        impl_downcast! {
+            @parse_opts $kind $trait_ $gen $params $types $preds [$($opts)* { unchecked }] $($rest)*
+        }
+    };
+    (@parse_opts $kind:tt $trait_:tt $gen:tt $params:tt $types:tt $preds:tt [$($opts:tt)*]
+        no_inline $($rest:tt)*
+    ) => {
+        impl_downcast! {
+            @parse_opts $kind $trait_ $gen $params $types $preds [$($opts)* { no_inline }] $($rest)*
+        }
+    };
+    // Further traits, separated by semicolons, each of which is expanded independently.
+    (@parse_opts $kind:tt $trait_:tt $gen:tt $params:tt $types:tt $preds:tt $opts:tt ; $($rest:tt)*) => {
+        impl_downcast! { @parse_opts $kind $trait_ $gen $params $types $preds $opts }
+        impl_downcast! { $($rest)* }
+    };
+    (@parse_opts [] $trait_:tt $gen:tt $params:tt $types:tt $preds:tt $opts:tt) => {
+        impl_downcast! {
+            @impl_attrs [] [#[inline]] [] $opts @impl_full $trait_ $params for $gen types $types where $preds opts $opts
+        }
+    };
+    (@parse_opts [sync] $trait_:tt $gen:tt $params:tt $types:tt $preds:tt $opts:tt) => {
+        impl_downcast! {
+            @impl_attrs [] [#[inline]] [] $opts @impl_full_sync $trait_ $params for $gen types $types where $preds opts $opts
+        }
+    };
+    (@parse_opts [send] $trait_:tt $gen:tt $params:tt $types:tt $preds:tt $opts:tt) => {
+        impl_downcast! {
+            @impl_attrs [] [#[inline]] [] $opts @impl_full_send $trait_ $params for $gen types $types where $preds opts $opts
+        }
+    };
+
+    // Collects the attributes of the generated impls, of their methods (`#[inline]` unless
+    // `no_inline`) and of the other generated items (the `cfg` of `feature = "..."`) from the
+    // options, then appends them to the macro invocation that follows.
+    (@impl_attrs [$($attrs:tt)*] $inline:tt [$($cfg:tt)*] [] $($then:tt)*) => {
+        impl_downcast! { $($then)* attrs [$($attrs)*] inline $inline cfg [$($cfg)*] }
+    };
+    (@impl_attrs [$($attrs:tt)*] $inline:tt $cfg:tt [{ doc_hidden } $($opts:tt)*] $($then:tt)*) => {
+        impl_downcast! { @impl_attrs [$($attrs)* #[doc(hidden)]] $inline $cfg [$($opts)*] $($then)* }
+    };
+    (@impl_attrs $attrs:tt $inline:tt $cfg:tt [{ no_inline } $($opts:tt)*] $($then:tt)*) => {
+        impl_downcast! { @impl_attrs $attrs [#[cfg_attr(not(debug_assertions), inline)]] $cfg [$($opts)*] $($then)* }
+    };
+    (@impl_attrs [$($attrs:tt)*] $inline:tt [$($cfg:tt)*] [{ feature $feature:tt } $($opts:tt)*] $($then:tt)*) => {
+        impl_downcast! {
+            @impl_attrs [$($attrs)* #[cfg(feature = $feature)]] $inline [$($cfg)* #[cfg(feature = $feature)]]
+                [$($opts)*] $($then)*
+        }
+    };
+    (@impl_attrs $attrs:tt $inline:tt $cfg:tt [$opt:tt $($opts:tt)*] $($then:tt)*) => {
+        impl_downcast! { @impl_attrs $attrs $inline $cfg [$($opts)*] $($then)* }
+    };
+
+    // Moves the implementor of `single Trait => Type` to an option after the trait.
+    (@single [$($before:tt)*] => $($rest:tt)+) => { impl_downcast! { $($before)* single => $($rest)+ } };
+    (@single [$($before:tt)*] $tok:tt $($rest:tt)+) => { impl_downcast! { @single [$($before)* $tok] $($rest)+ } };
+
+    // Trait aliases (nightly `trait_alias`) combine several traits, which cannot form a single
+    // trait object type to implement the methods on.
+    (sync alias $($rest:tt)+) => { impl_downcast! { alias $($rest)+ } };
+    (send alias $($rest:tt)+) => { impl_downcast! { alias $($rest)+ } };
+    (alias $($rest:tt)+) => {
+        $crate::__std::compile_error!(
+            "`impl_downcast!` does not support trait aliases; invoke it on the underlying trait \
+             extending `Downcast` and use trait objects of that trait"
+        );
+    };
+    // Splits off plain traits in a list right away, which keeps long lists of them within the
+    // recursion limit. Other entries are split off once parsed.
+    (sync $trait_:ident ; $($rest:tt)*) => { impl_downcast! { sync $trait_ } impl_downcast! { $($rest)* } };
+    (send $trait_:ident ; $($rest:tt)*) => { impl_downcast! { send $trait_ } impl_downcast! { $($rest)* } };
+    ($trait_:ident ; $($rest:tt)*) => { impl_downcast! { $trait_ } impl_downcast! { $($rest)* } };
+    () => {};
+    (sync single $($rest:tt)+) => { impl_downcast! { @single [sync] $($rest)+ } };
+    (send single $($rest:tt)+) => { impl_downcast! { @single [send] $($rest)+ } };
+    (single $($rest:tt)+) => { impl_downcast! { @single [] $($rest)+ } };
+    (sync concrete $($rest:tt)+) => { impl_downcast! { @parse [sync] [concrete] $($rest)+ } };
+    (concrete sync $($rest:tt)+) => { impl_downcast! { sync concrete $($rest)+ } };
+    (send concrete $($rest:tt)+) => { impl_downcast! { @parse [send] [concrete] $($rest)+ } };
+    (concrete send $($rest:tt)+) => { impl_downcast! { send concrete $($rest)+ } };
+    (concrete $($rest:tt)+) => { impl_downcast! { @parse [] [concrete] $($rest)+ } };
+    (sync $($rest:tt)+) => { impl_downcast! { @parse [sync] [] $($rest)+ } };
+    (send $($rest:tt)+) => { impl_downcast! { @parse [send] [] $($rest)+ } };
+    ($trait_:ident $($rest:tt)*) => { impl_downcast! { @parse [] [] $trait_ $($rest)* } };
+    (:: $($rest:tt)+) => { impl_downcast! { @parse [] [] :: $($rest)+ } };
+}
+
+/// Adds a method to a struct that downcasts one of its `Pin<Box<dyn Trait>>` fields (where
+/// `Trait: Downcast`) while keeping it pinned.
+///
+/// `impl_pin_field_downcast!(Machine, state: State => state_as)` generates
+/// `fn state_as<T: State>(&self) -> Option<Pin<&T>>` on `Machine`, which is `Some` if
+/// `self.state` wraps a `T`. It calls the `downcast_pin_ref` method generated by `impl_downcast!`
+/// with the `unchecked` option, which the trait thus needs.
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate downcast_rs;
+/// # use downcast_rs::Downcast;
+/// # use std::pin::Pin;
+/// trait State: Downcast {}
+/// impl_downcast!(State unchecked);
+/// struct Idle;
+/// impl State for Idle {}
+///
+/// struct Machine { state: Pin<Box<dyn State>> }
+/// impl_pin_field_downcast!(Machine, state: State => state_as);
+///
+/// # fn main() {
+/// let machine = Machine { state: Box::pin(Idle) };
+/// assert!(machine.state_as::<Idle>().is_some());
+/// # }
+/// ```
+#[macro_export]
+macro_rules! impl_pin_field_downcast {
+    ($ty:ty, $field:ident : $trait_:path => $method:ident) => {
+        impl $ty {
+            /// Returns the pinned field's object if it is of type `__T`, or `None` if it isn't.
+            #[allow(dead_code)]
+            pub fn $method<__T: $trait_>(&self)
+                -> $crate::__std::option::Option<$crate::__std::pin::Pin<&__T>>
+            {
+                self.$field.as_ref().downcast_pin_ref::<__T>()
+            }
+        }
+    };
+}
+
+/// Adds a `downcast_ref` method to a struct that delegates to one of its fields holding a trait
+/// object (where `Trait: Downcast`), e.g. a `Box<dyn Trait>`, for wrapper structs.
+///
+/// `impl_field_downcast!(Wrapper, inner: Trait)` generates
+/// `fn downcast_ref<T: Trait>(&self) -> Option<&T>` on `Wrapper`, which is `Some` if `self.inner`
+/// holds a `T`. For a generic struct, declare its type parameters as in
+/// `impl_field_downcast!(impl<T> Wrapper<T>, inner: Trait)`.
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate downcast_rs;
+/// # use downcast_rs::Downcast;
+/// trait Plugin: Downcast {}
+/// struct Logger;
+/// impl Plugin for Logger {}
+///
+/// struct Slot { name: &'static str, inner: Box<dyn Plugin> }
+/// impl_field_downcast!(Slot, inner: Plugin);
+///
+/// # fn main() {
+/// let slot = Slot { name: "log", inner: Box::new(Logger) };
+/// assert!(slot.downcast_ref::<Logger>().is_some());
+/// # }
+/// ```
+#[macro_export(local_inner_macros)]
+macro_rules! impl_field_downcast {
+    (impl<$($gen:ident),* $(,)*> $ty:ty, $field:ident : $trait_:path) => {
+        impl<$($gen),*> $ty {
+            /// Returns the field's object if it is of type `__T`, or `None` if it isn't.
+            #[allow(dead_code)]
+            pub fn downcast_ref<__T: $trait_>(&self) -> $crate::__std::option::Option<&__T> {
+                $crate::try_ref::<_, __T>(&*self.$field)
+            }
+        }
+    };
+    ($ty:ty, $field:ident : $trait_:path) => {
+        impl_field_downcast!(impl<> $ty, $field: $trait_);
+    };
+}
+
+/// Dispatches on the concrete type of a trait object (where `Trait: Downcast`) by comparing its
+/// `TypeId` once against each listed type, without re-checking the type when downcasting.
+///
+/// Each arm is either `Type => expr` or `name: Type => expr`, which binds `name: &Type` to the
+/// object. The final `_ => expr` arm runs if the object is of none of the listed types.
+/// The trait object is given by an expression dereferencing to it, such as a `&dyn Trait` or a
+/// `Box<dyn Trait>`. Pass `*item` for an `item: &Box<dyn Trait>`, or the `Box` itself, which is
+/// `Any` too, would be dispatched on.
+///
+/// The downcasts are pointer casts within `downcast-rs`, which are sound since the `TypeId`
+/// matched, so the expansion contains no `unsafe` code.
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate downcast_rs;
+/// # use downcast_rs::Downcast;
+/// trait Shape: Downcast {}
+/// struct Square(f64);
+/// impl Shape for Square {}
+/// struct Circle(f64);
+/// impl Shape for Circle {}
+/// struct Dot;
+/// impl Shape for Dot {}
+///
+/// fn area(shape: &dyn Shape) -> f64 {
+///     dispatch_downcast!(shape, {
+///         square: Square => square.0 * square.0,
+///         circle: Circle => 3.14 * circle.0 * circle.0,
+///         _ => 0.0,
+///     })
+/// }
+/// # fn main() {
+/// assert_eq!(area(&Square(2.0)), 4.0);
+/// assert_eq!(area(&Dot), 0.0);
+/// # }
+/// ```
+#[macro_export(local_inner_macros)]
+macro_rules! dispatch_downcast {
+    (@arms $obj:ident { _ => $fallback:expr $(,)* }) => { $fallback };
+    (@arms $obj:ident { $name:ident : $ty:ty => $e:expr, $($rest:tt)+ }) => {
+        if let $crate::__std::option::Option::Some($name) = $obj.get::<$ty>() {
+            $e
+        } else {
+            dispatch_downcast! { @arms $obj { $($rest)+ } }
+        }
+    };
+    (@arms $obj:ident { $ty:ty => $e:expr, $($rest:tt)+ }) => {
+        if $obj.is::<$ty>() {
+            $e
+        } else {
+            dispatch_downcast! { @arms $obj { $($rest)+ } }
+        }
+    };
+    ($base:expr, { $($arms:tt)+ }) => {{
+        let __obj = $crate::__Dispatch::new(&*$base);
+        dispatch_downcast! { @arms __obj { $($arms)+ } }
+    }};
+}
+
+/// Makes objects of a trait that cannot be changed to extend `Downcast`, such as one from another
+/// crate, downcastable.
+///
+/// `wrap_downcast!(pub foreign::Shape as LocalShape)` declares a local trait `LocalShape`
+/// extending both `foreign::Shape` and `Downcast`, implements it for every `'static` type
+/// implementing `foreign::Shape`, and invokes `impl_downcast!` on it. Storing objects as
+/// `Box<dyn LocalShape>` instead of `Box<dyn foreign::Shape>` then allows both calling the foreign
+/// trait's methods and downcasting. `as_wrapped` converts back into a `&dyn foreign::Shape`.
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate downcast_rs;
+/// mod foreign {
+///     pub trait Shape { fn area(&self) -> f64; }
+/// }
+/// wrap_downcast!(foreign::Shape as LocalShape);
+///
+/// struct Square(f64);
+/// impl foreign::Shape for Square { fn area(&self) -> f64 { self.0 * self.0 } }
+///
+/// # fn main() {
+/// let shape: Box<dyn LocalShape> = Box::new(Square(2.0));
+/// assert_eq!(shape.area(), 4.0);
+/// assert_eq!(shape.downcast_ref::<Square>().unwrap().0, 2.0);
+/// # }
+/// ```
+#[macro_export(local_inner_macros)]
+macro_rules! wrap_downcast {
+    ($vis:vis $foreign:path as $name:ident) => {
+        /// Downcastable version of a foreign trait, generated by `wrap_downcast!`.
+        $vis trait $name: $foreign + $crate::Downcast {
+            /// Returns the object as a trait object of the wrapped trait.
+            fn as_wrapped(&self) -> &(dyn $foreign + 'static);
+        }
+        impl<__T: $foreign + $crate::__std::any::Any> $name for __T {
+            fn as_wrapped(&self) -> &(dyn $foreign + 'static) { self }
+        }
+        impl_downcast!($name);
+    };
+}
+
+/// Registers a concrete type as implementing a trait extending `Downcast`, so that it is listed by
+/// `registered_types::<dyn Trait>()`. Requires the `inventory` feature.
+///
+/// `register_downcast!(Trait, Type)` may be invoked anywhere in any crate linked into the final
+/// binary; the registrations are collected at link time by the `inventory` crate. The trait must
+/// have been passed to `impl_downcast!`.
+#[cfg(feature = "inventory")]
+#[macro_export]
+macro_rules! register_downcast {
+    ($trait_:path, $ty:ty) => {
+        $crate::__inventory::submit! {
+            $crate::DowncastRegistration::__new::<dyn $trait_, $ty>()
+        }
+    };
+}
+
+/// Returns true if a trait object (where `Trait` was passed to `impl_downcast!`) is of any of the
+/// `|`-separated types, e.g. `matches_type!(base, Foo | Bar)`.
+///
+/// Shorthand for `base.is::<Foo>() || base.is::<Bar>()`, evaluating `base` only once.
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate downcast_rs;
+/// # use downcast_rs::Downcast;
+/// trait Base: Downcast {}
+/// impl_downcast!(Base);
+/// struct Foo;
+/// impl Base for Foo {}
+/// struct Bar;
+/// impl Base for Bar {}
+///
+/// # fn main() {
+/// let base: Box<dyn Base> = Box::new(Bar);
+/// assert!(matches_type!(base, Foo | Bar));
+/// # }
+/// ```
+#[macro_export]
+macro_rules! matches_type {
+    ($base:expr, $($ty:ty)|+) => {{
+        let __obj = &$base;
+        false $(|| __obj.is::<$ty>())+
+    }};
+}
+
+/// Downcasts each trait object (where `Trait: Downcast`) of a fixed-size heterogeneous group to the
+/// type at the same position, e.g. `downcast_tuple!((a, b) as (Foo, Bar))`, returning
+/// `Option<(&Foo, &Bar)>`, which is `None` if any of them is of another type.
+///
+/// As with `dispatch_downcast!`, each trait object is given by an expression dereferencing to it,
+/// such as a `Box<dyn Trait>`.
+///
+/// ```
+/// # #[macro_use]
+/// # extern crate downcast_rs;
+/// # use downcast_rs::Downcast;
+/// trait Base: Downcast {}
+/// struct Foo(u32);
+/// impl Base for Foo {}
+/// struct Bar(&'static str);
+/// impl Base for Bar {}
+///
+/// # fn main() {
+/// let layout: (Box<dyn Base>, Box<dyn Base>) = (Box::new(Foo(1)), Box::new(Bar("bar")));
+/// let (foo, bar) = downcast_tuple!((layout.0, layout.1) as (Foo, Bar)).unwrap();
+/// assert_eq!((foo.0, bar.0), (1, "bar"));
+/// assert!(downcast_tuple!((layout.0, layout.1) as (Bar, Foo)).is_none());
+/// # }
+/// ```
+#[macro_export(local_inner_macros)]
+macro_rules! downcast_tuple {
+    (($($base:expr),+ $(,)*) as ($($ty:ty),+ $(,)*)) => {
+        downcast_tuple! { @bind [] [] $(($base, $ty))+ }
+    };
+    // Each step binds a fresh (hygienic) `__obj` for its position.
+    (@bind [$($obj:ident)*] [$($res:tt)*] ($base:expr, $ty:ty) $($rest:tt)*) => {
+        downcast_tuple! { @bind [$($obj)* __obj] [$($res)* ($crate::try_ref::<_, $ty>(&*$base))] $($rest)* }
+    };
+    (@bind [$($obj:ident)*] [$($res:tt)*]) => {
+        match ($($res,)*) {
+            ($($crate::__std::option::Option::Some($obj),)*) => $crate::__std::option::Option::Some(($($obj,)*)),
+            _ => $crate::__std::option::Option::None,
+        }
+    };
+}
+
+
+/// Checks that invalid uses of the macro are rejected at compile time.
+#[cfg(doctest)]
+mod compile_fail {
+    /// `single` requires the type to implement the trait.
+    ///
+    /// ```compile_fail,E0277
+    /// # #[macro_use]
+    /// # extern crate downcast_rs;
+    /// # use downcast_rs::{Downcast, SingleImpl};
+    /// trait Base: Downcast + SingleImpl<Foo> {}
+    /// struct Foo;
+    /// impl_downcast!(single Base => Foo);
+    /// # fn main() {}
+    /// ```
+    struct SingleRequiresImplementor;
+
+    /// `single` requires the trait to be sealed by extending `SingleImpl`.
+    ///
+    /// ```compile_fail,E0277
+    /// # #[macro_use]
+    /// # extern crate downcast_rs;
+    /// # use downcast_rs::Downcast;
+    /// trait Base: Downcast {}
+    /// impl_downcast!(single Base => Foo);
+    /// struct Foo;
+    /// impl Base for Foo {}
+    /// # fn main() {}
+    /// ```
+    struct SingleRequiresSeal;
+
+    /// A trait sealed by `SingleImpl` rejects a second implementor.
+    ///
+    /// ```compile_fail,E0277
+    /// # #[macro_use]
+    /// # extern crate downcast_rs;
+    /// # use downcast_rs::{Downcast, SingleImpl};
+    /// trait Base: Downcast + SingleImpl<Foo> {}
+    /// impl_downcast!(single Base => Foo);
+    /// struct Foo;
+    /// impl Base for Foo {}
+    /// struct Bar;
+    /// impl Base for Bar {}
+    /// # fn main() {}
+    /// ```
+    struct SingleRejectsSecondImplementor;
+
+    /// `SingleImpl` cannot be implemented outside of `downcast-rs`.
+    ///
+    /// ```compile_fail,E0277
+    /// # use downcast_rs::SingleImpl;
+    /// struct Foo;
+    /// struct Bar;
+    /// impl SingleImpl<Foo> for Bar {}
+    /// # fn main() {}
+    /// ```
+    struct SingleImplSealed;
+
+    /// A concrete associated type cannot be an `impl Trait`; it must be named via an alias.
+    ///
+    /// ```compile_fail
+    /// # #[macro_use]
+    /// # extern crate downcast_rs;
+    /// # use downcast_rs::Downcast;
+    /// trait Base: Downcast { type H; }
+    /// impl_downcast!(concrete Base assoc H = impl Clone);
+    /// # fn main() {}
+    /// ```
+    struct AssocImplTraitNeedsAlias;
+
+    /// Trait aliases are rejected in favor of the underlying trait.
+    ///
+    /// ```compile_fail
+    /// # #[macro_use]
+    /// # extern crate downcast_rs;
+    /// # use downcast_rs::Downcast;
+    /// trait Base: Downcast {}
+    /// impl_downcast!(alias Base);
+    /// # fn main() {}
+    /// ```
+    struct TraitAliasRejected;
+
+    /// `clone => ...` requires every implementor to be `Clone`.
+    ///
+    /// ```compile_fail,E0277
+    /// # #[macro_use]
+    /// # extern crate downcast_rs;
+    /// # use downcast_rs::Downcast;
+    /// trait Base: Downcast + BaseClone {}
+    /// impl_downcast!(Base clone => BaseClone);
+    /// struct NotClone;
+    /// impl Base for NotClone {}
+    /// # fn main() {}
+    /// ```
+    struct CloneRequiresCloneImplementors;
+
+    /// Unknown names in `extras => [...]` are rejected.
+    ///
+    /// ```compile_fail
+    /// # #[macro_use]
+    /// # extern crate downcast_rs;
+    /// # use downcast_rs::Downcast;
+    /// trait Base: Downcast {}
+    /// impl_downcast!(Base extras => [project, no_such_method]);
+    /// # fn main() {}
+    /// ```
+    struct UnknownExtraRejected;
+
+    /// The `has_capability` extra requires the `once-lock` feature.
+    ///
+    /// ```compile_fail
+    /// # #[macro_use]
+    /// # extern crate downcast_rs;
+    /// # use downcast_rs::Downcast;
+    /// trait Base: Downcast {}
+    /// impl_downcast!(Base extras => [has_capability]);
+    /// # fn main() {}
+    /// ```
+    #[cfg(not(feature = "once-lock"))]
+    struct HasCapabilityRequiresOnceLock;
+
+    /// The `concrete_type_code` extra requires the `once-lock` feature.
+    ///
+    /// ```compile_fail
+    /// # #[macro_use]
+    /// # extern crate downcast_rs;
+    /// # use downcast_rs::Downcast;
+    /// trait Base: Downcast {}
+    /// impl_downcast!(Base extras => [concrete_type_code]);
+    /// # fn main() {}
+    /// ```
+    #[cfg(not(feature = "once-lock"))]
+    struct ConcreteTypeCodeRequiresOnceLock;
+
+    /// `send` is rejected on traits that don't extend `DowncastSend`.
+    ///
+    /// ```compile_fail,E0277
+    /// # #[macro_use]
+    /// # extern crate downcast_rs;
+    /// # use downcast_rs::Downcast;
+    /// trait Base: Downcast {}
+    /// impl_downcast!(send Base);
+    /// # fn main() {}
+    /// ```
+    struct SendWithoutDowncastSendRejected;
+
+    /// Traits that aren't object-safe are rejected at the macro invocation.
+    ///
+    /// ```compile_fail,E0038
+    /// # #[macro_use]
+    /// # extern crate downcast_rs;
+    /// # use downcast_rs::Downcast;
+    /// trait Base: Downcast {
+    ///     fn generic<T>(&self, t: T);
+    /// }
+    /// impl_downcast!(Base);
+    /// # fn main() {}
+    /// ```
+    struct ObjectUnsafeRejected;
+
+    /// Downcasting isn't possible in const contexts, even for trait objects created in constants.
+    ///
+    /// ```compile_fail,E0015
+    /// # #[macro_use]
+    /// # extern crate downcast_rs;
+    /// # use downcast_rs::Downcast;
+    /// trait Base: Downcast {}
+    /// impl_downcast!(Base);
+    /// struct Foo;
+    /// impl Base for Foo {}
+    /// const OBJ: &dyn Base = &Foo;
+    /// const IS_FOO: bool = OBJ.is::<Foo>();
+    /// # fn main() {}
+    /// ```
+    struct ConstDowncastRejected;
+
+    /// `DowncastIteratorExt` rejects iterators over `&Box<dyn Trait>`, whose items are the boxes.
+    /// Depending on the toolchain, the error is E0277 or E0599, so neither is checked.
+    ///
+    /// ```compile_fail
+    /// # #[macro_use]
+    /// # extern crate downcast_rs;
+    /// # use downcast_rs::{Downcast, DowncastIteratorExt};
+    /// trait Base: Downcast {}
+    /// impl_downcast!(Base);
+    /// struct Foo;
+    /// impl Base for Foo {}
+    /// fn main() {
+    ///     let items: Vec<Box<dyn Base>> = vec![Box::new(Foo)];
+    ///     let _ = items.iter().filter_downcast_ref::<Foo>().count();
+    /// }
+    /// ```
+    struct IteratorOverBoxesRejected;
+}
+
+#[cfg(all(test, feature = "sync"))]
+mod test {
+    #[cfg(not(feature = "std"))]
+    use __alloc::{boxed::Box, string::String, vec, vec::Vec};
+
+    macro_rules! test_mod {
+        (
+            $test_mod_name:ident,
+            trait $base_trait:path { $($base_impl:tt)* },
+            non_sync: { $($non_sync_def:tt)+ },
+            sync: { $($sync_def:tt)+ }
+        ) => {
+            test_mod! {
+                $test_mod_name,
+                trait $base_trait { $($base_impl:tt)* },
+                type dyn $base_trait,
+                non_sync: { $($non_sync_def)* },
+                sync: { $($sync_def)* }
+            }
+        };
+
+        (
+            $test_mod_name:ident,
+            trait $base_trait:path { $($base_impl:tt)* },
+            type $base_type:ty,
+            non_sync: { $($non_sync_def:tt)+ },
+            sync: { $($sync_def:tt)+ }
+        ) => {
+            mod $test_mod_name {
+                test_mod!(
+                    @test
+                    $test_mod_name,
+                    test_name: test_non_sync,
+                    trait $base_trait { $($base_impl)* },
+                    type $base_type,
+                    { $($non_sync_def)+ },
+                    []);
+
+                test_mod!(
+                    @test
+                    $test_mod_name,
+                    test_name: test_sync,
+                    trait $base_trait { $($base_impl)* },
+                    type $base_type,
+                    { $($sync_def)+ },
+                    [{
+                        // Fail to convert Arc<Base> into Arc<Bar>.
+                        let arc: $crate::__alloc::sync::Arc<$base_type> = $crate::__alloc::sync::Arc::new(Foo(42));
+                        let res = arc.downcast_arc::<Bar>();
+                        assert!(res.is_err());
+                        let arc = res.unwrap_err();
+                        // Convert Arc<Base> into Arc<Foo>.
+                        assert_eq!(
+                            42, arc.downcast_arc::<Foo>().map_err(|_| "Shouldn't happen.").unwrap().0);
+                    }]);
+            }
+        };
+
+        (
+            @test
+            $test_mod_name:ident,
+            test_name: $test_name:ident,
+            trait $base_trait:path { $($base_impl:tt)* },
+            type $base_type:ty,
+            { $($def:tt)+ },
+            [ $($more_tests:block)* ]
+        ) => {
+            #[test]
+            fn $test_name() {
+                #[allow(unused_imports)]
+                use super::super::{Downcast, DowncastSync};
+
+                // Should work even if standard objects (especially those in the prelude) are
+                // aliased to something else.
+                #[allow(dead_code)] struct Any;
+                #[allow(dead_code)] struct Arc;
+                #[allow(dead_code)] struct Box;
+                #[allow(dead_code)] struct Option;
+                #[allow(dead_code)] struct Result;
+                #[allow(dead_code)] struct Rc;
+                #[allow(dead_code)] struct Send;
+                #[allow(dead_code)] struct Sync;
+
+                // A trait that can be downcast.
+                $($def)*
+
+                // Concrete type implementing Base.
+                #[derive(Debug)]
+                struct Foo(u32);
+                impl $base_trait for Foo { $($base_impl)* }
+                #[derive(Debug)]
+                struct Bar(f64);
+                impl $base_trait for Bar { $($base_impl)* }
+
+                // Functions that can work on references to Base trait objects.
+                fn get_val(base: &$crate::__alloc::boxed::Box<$base_type>) -> u32 {
+                    match base.downcast_ref::<Foo>() {
+                        Some(val) => val.0,
+                        None => 0
+                    }
+                }
+                fn set_val(base: &mut $crate::__alloc::boxed::Box<$base_type>, val: u32) {
+                    if let Some(foo) = base.downcast_mut::<Foo>() {
+                        foo.0 = val;
+                    }
+                }
+
+                let mut base: $crate::__alloc::boxed::Box<$base_type> = $crate::__alloc::boxed::Box::new(Foo(42));
+                assert_eq!(get_val(&base), 42);
+
+                // Try sequential downcasts.
+                if let Some(foo) = base.downcast_ref::<Foo>() {
+                    assert_eq!(foo.0, 42);
+                } else if let Some(bar) = base.downcast_ref::<Bar>() {
+                    assert_eq!(bar.0, 42.0);
+                }
+
+                set_val(&mut base, 6*9);
+                assert_eq!(get_val(&base), 6*9);
 
                 assert!(base.is::<Foo>());
 
-                // Fail to convert Box<Base> into Box<Bar>.
-                let res = base.downcast::<Bar>();
-                assert!(res.is_err());
-                let base = res.unwrap_err();
-                // Convert Box<Base> into Box<Foo>.
-                assert_eq!(
-                    6*9, base.downcast::<Foo>().map_err(|_| "Shouldn't happen.").unwrap().0);
+                // Fail to convert Box<Base> into Box<Bar>.
+                let res = base.downcast::<Bar>();
+                assert!(res.is_err());
+                let base = res.unwrap_err();
+                // Convert Box<Base> into Box<Foo>.
+                assert_eq!(
+                    6*9, base.downcast::<Foo>().map_err(|_| "Shouldn't happen.").unwrap().0);
+
+                // Fail to convert Rc<Base> into Rc<Bar>.
+                let rc: $crate::__alloc::rc::Rc<$base_type> = $crate::__alloc::rc::Rc::new(Foo(42));
+                let res = rc.downcast_rc::<Bar>();
+                assert!(res.is_err());
+                let rc = res.unwrap_err();
+                // Convert Rc<Base> into Rc<Foo>.
+                assert_eq!(
+                    42, rc.downcast_rc::<Foo>().map_err(|_| "Shouldn't happen.").unwrap().0);
+
+                $($more_tests)*
+            }
+        };
+        (
+            $test_mod_name:ident,
+            trait $base_trait:path { $($base_impl:tt)* },
+            non_sync: { $($non_sync_def:tt)+ },
+            sync: { $($sync_def:tt)+ }
+        ) => {
+            test_mod! {
+                $test_mod_name,
+                trait $base_trait { $($base_impl:tt)* },
+                type $base_trait,
+                non_sync: { $($non_sync_def)* },
+                sync: { $($sync_def)* }
+            }
+        };
+
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    #[allow(dead_code)]
+    fn downcast_error_with_question_mark() {
+        use std::error::Error;
+        use super::Downcast;
+
+        trait Base: Downcast {}
+        impl_downcast!(Base extras => [try_downcast_ref]);
+        #[derive(Debug)]
+        struct Foo(u32);
+        impl Base for Foo {}
+        #[derive(Debug)]
+        struct Bar;
+        impl Base for Bar {}
+
+        fn get_foo(base: &dyn Base) -> Result<u32, Box<dyn Error>> {
+            let foo = base.try_downcast_ref::<Foo>()?;
+            Ok(foo.0)
+        }
+
+        assert_eq!(get_foo(&Foo(42)).unwrap(), 42);
+        let err = get_foo(&Bar).unwrap_err();
+        let message = err.to_string();
+        assert!(message.contains("::Foo"));
+        assert!(message.contains("::Bar"));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    #[allow(dead_code)]
+    fn downcast_error_fields_and_bounds() {
+        use std::error::Error;
+        use super::{Downcast, DowncastError, DowncastErrorKind};
+
+        trait Base: Downcast + Send + Sync {}
+        impl_downcast!(Base extras => [try_downcast]);
+        #[derive(Debug)]
+        struct Foo(u32);
+        impl Base for Foo {}
+        #[derive(Debug)]
+        struct Bar;
+        impl Base for Bar {}
+
+        fn assert_send_sync_static<T: Send + Sync + 'static>(_: &T) {}
+
+        let base: Box<dyn Base> = Box::new(Foo(7));
+        let err: DowncastError<Box<dyn Base>> = base.try_downcast::<Bar>().unwrap_err();
+        assert_send_sync_static(&err);
+        assert_eq!(err.kind(), DowncastErrorKind::TypeMismatch);
+        assert!(err.expected_type_name().ends_with("::Bar"));
+        assert!(err.actual_type_name().ends_with("::Foo"));
+        assert!(format!("{:?}", err).contains("TypeMismatch"));
+        let base = err.into_inner();
+        assert_eq!(base.downcast_ref::<Foo>().unwrap().0, 7);
+
+        // Closures defined in the same function are distinct types of the same name.
+        fn try_downcast_like<T: Base>(base: Box<dyn Base>, _: &T) -> DowncastError<Box<dyn Base>> {
+            base.try_downcast::<T>().map(|_| ()).unwrap_err()
+        }
+        let (first, second) = (|| 1, || 2);
+        impl<F: Fn() -> u32 + Send + Sync + 'static> Base for F {}
+        let err = try_downcast_like(Box::new(first), &second);
+        assert_eq!(err.kind(), DowncastErrorKind::NameCollision);
+        assert_eq!(err.expected_type_name(), err.actual_type_name());
+        assert!(err.to_string().ends_with(", a distinct type of the same name"));
+
+        // Hence it can cross threads as a boxed error.
+        let err = base.try_downcast::<Bar>().map(|_| ()).unwrap_err();
+        let err: Box<dyn Error + Send + Sync> = Box::new(err);
+        let message = std::thread::spawn(move || err.to_string()).join().unwrap();
+        assert!(message.contains("::Foo"));
+    }
+
+    /// Checks (and, under `cargo miri test`, validates against strict provenance and stacked
+    /// borrows) that the owned `downcast` neither moves nor reallocates the object: a successful
+    /// downcast yields a `Box` whose data pointer is the original allocation, and a failed one
+    /// hands back a `Box` pointing at that same allocation.
+    #[test]
+    #[allow(dead_code)]
+    fn downcast_preserves_allocation() {
+        use super::Downcast;
+
+        trait Base: Downcast {}
+        impl_downcast!(Base);
+        struct Foo([u64; 4]);
+        impl Base for Foo {}
+        struct Bar;
+        impl Base for Bar {}
+
+        let base: Box<dyn Base> = Box::new(Foo([1, 2, 3, 4]));
+        let addr = &*base as *const dyn Base as *const ();
+
+        let base = base.downcast::<Bar>().map(|_| ()).unwrap_err();
+        assert_eq!(&*base as *const dyn Base as *const (), addr);
+
+        let foo = base.downcast::<Foo>().map_err(|_| "Shouldn't happen.").unwrap();
+        assert_eq!(&*foo as *const Foo as *const (), addr);
+        assert_eq!(foo.0, [1, 2, 3, 4]);
+    }
+
+    #[test]
+    #[allow(dead_code)]
+    fn downcast_inline() {
+        use super::Downcast;
+        use __alloc::rc::Rc;
+
+        trait Base: Downcast {}
+        impl_downcast!(Base extras => [downcast_inline]);
+        #[derive(Clone, Copy, Debug, PartialEq)]
+        struct Small(u8);
+        impl Base for Small {}
+        // Counts live copies through the `Rc` to detect leaks and double drops.
+        struct Large([u64; 64], Rc<()>);
+        impl Base for Large {}
+
+        let base: Box<dyn Base> = Box::new(Small(7));
+        let base = base.downcast_inline::<Large>().map(|_| ()).unwrap_err();
+        assert_eq!(base.downcast_inline::<Small>().map_err(|_| "Shouldn't happen."), Ok(Small(7)));
+
+        let counter = Rc::new(());
+        let base: Box<dyn Base> = Box::new(Large([3; 64], counter.clone()));
+        let base = base.downcast_inline::<Small>().map(|_| ()).unwrap_err();
+        assert_eq!(Rc::strong_count(&counter), 2);
+        let large = base.downcast_inline::<Large>().map_err(|_| "Shouldn't happen.").unwrap();
+        assert_eq!(large.0[63], 3);
+        assert_eq!(Rc::strong_count(&counter), 2);
+        drop(large);
+        assert_eq!(Rc::strong_count(&counter), 1);
+    }
+
+    #[test]
+    #[allow(dead_code)]
+    fn concrete_layout() {
+        use super::Downcast;
+        use super::__std::alloc::Layout;
+
+        trait Base: Downcast {}
+        impl_downcast!(Base extras => [concrete_layout]);
+        // Unlike that of a `Box<dyn Base>`, its layout is 3 bytes aligned to 1.
+        struct Foo([u8; 3]);
+        impl Base for Foo {}
+        struct Bar;
+        impl Base for Bar {}
+
+        let boxed: Box<dyn Base> = Box::new(Foo([1, 2, 3]));
+        let base: &dyn Base = &*boxed;
+        assert_eq!(base.concrete_layout(), Layout::new::<Foo>());
+        assert_ne!(base.concrete_layout(), Layout::new::<Box<dyn Base>>());
+        // Called on the `Box` itself, it still describes the object.
+        assert_eq!(boxed.concrete_layout(), Layout::new::<Foo>());
+        let base: &dyn Base = &Bar;
+        assert_eq!(base.concrete_layout(), Layout::new::<Bar>());
+        assert_eq!(base.concrete_layout().size(), 0);
+    }
+
+    #[test]
+    #[allow(dead_code)]
+    fn type_name() {
+        use super::Downcast;
+        use super::__std::any::type_name;
+
+        trait Base: Downcast {}
+        impl_downcast!(Base extras => [type_name]);
+        struct Foo;
+        impl Base for Foo {}
+
+        let boxed: Box<dyn Base> = Box::new(Foo);
+        let base: &dyn Base = &*boxed;
+        assert_eq!(base.type_name(), type_name::<Foo>());
+        let base: &dyn Base = &Foo;
+        assert_eq!(base.type_name(), type_name::<Foo>());
+        // Called on the `Box` itself, it still names the object's type.
+        assert_eq!(boxed.type_name(), type_name::<Foo>());
+    }
+
+    #[test]
+    #[allow(dead_code)]
+    fn trait_methods_named_like_extras() {
+        use super::Downcast;
+
+        // Without `extras`, trait methods of the same names as the extras are called as usual.
+        trait Base: Downcast {
+            fn type_name(&self) -> &'static str;
+            fn try_downcast(&self) -> u32;
+        }
+        impl_downcast!(Base);
+        struct Foo;
+        impl Base for Foo {
+            fn type_name(&self) -> &'static str { "foo" }
+            fn try_downcast(&self) -> u32 { 7 }
+        }
+
+        let base: Box<dyn Base> = Box::new(Foo);
+        assert_eq!(base.type_name(), "foo");
+        assert_eq!(base.try_downcast(), 7);
+    }
+
+    #[test]
+    fn any_ext_concrete_layout() {
+        use super::AnyExt;
+        use super::__std::alloc::Layout;
+        use super::__std::any::Any;
+
+        let value: (u8, u64) = (1, 2);
+        let any: &dyn Any = &value;
+        assert_eq!(any.concrete_layout(), Layout::new::<(u8, u64)>());
+        let any: &(dyn Any + Send) = &[0u16; 3];
+        assert_eq!(any.concrete_layout(), Layout::new::<[u16; 3]>());
+        let boxed: Box<dyn Any + Send + Sync> = Box::new(());
+        assert_eq!((*boxed).concrete_layout().size(), 0);
+    }
+
+    #[test]
+    #[allow(dead_code)]
+    fn downcast_ref_interior_mutability() {
+        use super::Downcast;
+        use super::__std::cell::{Cell, RefCell};
+
+        trait Base: Downcast {}
+        impl_downcast!(Base);
+        // Neither `Sync` nor immutable behind a shared reference.
+        struct Foo { count: Cell<u32>, log: RefCell<Vec<u32>> }
+        impl Base for Foo {}
+
+        let base: Box<dyn Base> = Box::new(Foo { count: Cell::new(0), log: RefCell::new(Vec::new()) });
+        let shared: &dyn Base = &*base;
+        for _ in 0..3 {
+            let foo = shared.downcast_ref::<Foo>().unwrap();
+            foo.count.set(foo.count.get() + 1);
+            foo.log.borrow_mut().push(foo.count.get());
+        }
+        let foo = base.downcast_ref::<Foo>().unwrap();
+        assert_eq!(foo.count.get(), 3);
+        assert_eq!(*foo.log.borrow(), [1, 2, 3]);
+    }
 
-                // Fail to convert Rc<Base> into Rc<Bar>.
-                let rc: $crate::__alloc::rc::Rc<$base_type> = $crate::__alloc::rc::Rc::new(Foo(42));
-                let res = rc.downcast_rc::<Bar>();
-                assert!(res.is_err());
-                let rc = res.unwrap_err();
-                // Convert Rc<Base> into Rc<Foo>.
-                assert_eq!(
-                    42, rc.downcast_rc::<Foo>().map_err(|_| "Shouldn't happen.").unwrap().0);
+    #[test]
+    #[allow(dead_code)]
+    fn blanket_impl_through_marker() {
+        use super::Downcast;
+        use super::__std::any::Any;
 
-                $($more_tests)*
+        trait Marker: Any {}
+        trait Base: Downcast {}
+        impl_downcast!(Base);
+        impl<T: Marker> Base for T {}
+        struct Foo(u32);
+        impl Marker for Foo {}
+        struct Bar;
+        impl Marker for Bar {}
+
+        let base: Box<dyn Base> = Box::new(Foo(1));
+        assert!(base.is::<Foo>());
+        assert!(!base.is::<Bar>());
+        assert_eq!(base.downcast_ref::<Foo>().unwrap().0, 1);
+        let base = base.downcast::<Bar>().map(|_| ()).unwrap_err();
+        assert_eq!(base.downcast::<Foo>().map_err(|_| "Shouldn't happen.").unwrap().0, 1);
+    }
+
+    #[test]
+    #[allow(dead_code)]
+    fn matches_type() {
+        use super::Downcast;
+
+        trait Base: Downcast {}
+        impl_downcast!(Base);
+        struct Foo;
+        impl Base for Foo {}
+        struct Bar;
+        impl Base for Bar {}
+        struct Baz;
+        impl Base for Baz {}
+
+        let base: Box<dyn Base> = Box::new(Bar);
+        assert!(matches_type!(base, Bar));
+        assert!(!matches_type!(base, Foo));
+        assert!(matches_type!(base, Foo | Bar));
+        assert!(matches_type!(*base, Bar | Baz));
+        assert!(!matches_type!(&*base, Foo | Baz));
+
+        let mut evaluations = 0;
+        assert!(!matches_type!({ evaluations += 1; &base }, Foo | Baz));
+        assert_eq!(evaluations, 1);
+    }
+
+    #[test]
+    #[allow(dead_code, unsafe_code)]
+    fn downcast_unchecked() {
+        use super::Downcast;
+
+        trait Base: Downcast {}
+        impl_downcast!(Base unchecked);
+        trait Generic<T>: Downcast {}
+        impl_downcast!(Generic<T> where T: Copy unchecked);
+        struct Foo(u32);
+        impl Base for Foo {}
+        impl<T: Copy> Generic<T> for Foo {}
+        // Zero-sized and over-aligned types must come out of the box intact too.
+        struct Bar;
+        impl Base for Bar {}
+        #[repr(align(64))]
+        struct Baz(u8);
+        impl Base for Baz {}
+
+        let base: Box<dyn Base> = Box::new(Foo(42));
+        let addr = &*base as *const dyn Base as *const u8;
+        assert!(base.is::<Foo>());
+        // SAFETY: The object was just checked to be a `Foo`.
+        let foo: Box<Foo> = unsafe { base.downcast_unchecked::<Foo>() };
+        assert_eq!(foo.0, 42);
+        assert_eq!(&*foo as *const Foo as *const u8, addr);
+
+        let base: Box<dyn Base> = Box::new(Bar);
+        // SAFETY: The object was created as a `Bar` above.
+        let _bar: Box<Bar> = unsafe { base.downcast_unchecked::<Bar>() };
+        let base: Box<dyn Base> = Box::new(Baz(7));
+        // SAFETY: The object was created as a `Baz` above.
+        assert_eq!(unsafe { base.downcast_unchecked::<Baz>() }.0, 7);
+
+        let generic: Box<dyn Generic<u8>> = Box::new(Foo(1));
+        // SAFETY: The object was created as a `Foo` above.
+        assert_eq!(unsafe { generic.downcast_unchecked::<Foo>() }.0, 1);
+    }
+
+    #[test]
+    #[allow(dead_code, unsafe_code)]
+    fn downcast_ref_mut_unchecked() {
+        use super::Downcast;
+        use super::__std::any::TypeId;
+
+        trait Base: Downcast {}
+        impl_downcast!(Base unchecked);
+        trait Generic<T>: Downcast {}
+        impl_downcast!(Generic<T> where T: Copy unchecked);
+        struct Foo(u32);
+        impl Base for Foo {}
+        impl<T: Copy> Generic<T> for Foo {}
+        #[repr(align(64))]
+        struct Bar(u8);
+        impl Base for Bar {}
+
+        // A dispatch table storing the resolved `TypeId` alongside each object.
+        let mut table: [(TypeId, Box<dyn Base>); 2] =
+            [(TypeId::of::<Foo>(), Box::new(Foo(1))), (TypeId::of::<Bar>(), Box::new(Bar(2)))];
+        for &mut (id, ref mut obj) in table.iter_mut() {
+            if id == TypeId::of::<Foo>() {
+                // SAFETY: The stored `TypeId` is that of the object.
+                unsafe { obj.downcast_mut_unchecked::<Foo>() }.0 += 10;
+            } else {
+                // SAFETY: The stored `TypeId` is that of the object.
+                unsafe { obj.downcast_mut_unchecked::<Bar>() }.0 += 20;
             }
+        }
+        let (first, second) = (&*table[0].1, &*table[1].1);
+        // SAFETY: The objects were created with these types above.
+        unsafe {
+            assert_eq!(first.downcast_ref_unchecked::<Foo>().0, 11);
+            assert_eq!(second.downcast_ref_unchecked::<Bar>().0, 22);
+            assert_eq!(
+                first.downcast_ref_unchecked::<Foo>() as *const Foo as *const u8,
+                first as *const dyn Base as *const u8);
+        }
+
+        let mut generic: Box<dyn Generic<u8>> = Box::new(Foo(3));
+        // SAFETY: The object was created as a `Foo` above.
+        unsafe {
+            generic.downcast_mut_unchecked::<Foo>().0 += 1;
+            assert_eq!(generic.downcast_ref_unchecked::<Foo>().0, 4);
+        }
+    }
+
+    #[test]
+    #[allow(dead_code)]
+    fn peek_downcast_ref_through_mut_box() {
+        use super::{peek_downcast_ref, Downcast};
+
+        trait Base: Downcast {}
+        impl_downcast!(Base);
+        struct Foo(u32);
+        impl Base for Foo {}
+        struct Bar;
+        impl Base for Bar {}
+
+        fn bump(slot: &mut Box<dyn Base>) -> u32 {
+            let old = match peek_downcast_ref::<Foo, _>(slot) {
+                Some(foo) => foo.0,
+                None => return 0,
+            };
+            // The shared reborrow has ended, so the mutable one is usable again.
+            *slot = Box::new(Foo(old + 1));
+            old
+        }
+
+        let mut base: Box<dyn Base> = Box::new(Foo(1));
+        assert_eq!(bump(&mut base), 1);
+        assert_eq!(peek_downcast_ref::<Foo, _>(&base).map(|foo| foo.0), Some(2));
+        assert!(peek_downcast_ref::<Bar, _>(&base).is_none());
+        // The box itself is never what gets downcast.
+        assert!(peek_downcast_ref::<Box<dyn Base>, _>(&base).is_none());
+    }
+
+    #[test]
+    #[allow(dead_code)]
+    fn clone_box() {
+        use super::Downcast;
+
+        trait Base: Downcast {}
+        impl_downcast!(Base clone => [Foo, Bar]);
+        #[derive(Clone)]
+        struct Foo(Vec<u32>);
+        impl Base for Foo {}
+        #[derive(Clone)]
+        struct Bar(String);
+        impl Base for Bar {}
+        struct Baz;
+        impl Base for Baz {}
+
+        let foo: Box<dyn Base> = Box::new(Foo(vec![1, 2]));
+        let mut foo_clone = foo.clone_box().unwrap();
+        foo_clone.downcast_mut::<Foo>().unwrap().0.push(3);
+        assert_eq!(foo.downcast_ref::<Foo>().unwrap().0, [1, 2]);
+        assert_eq!(foo_clone.downcast_ref::<Foo>().unwrap().0, [1, 2, 3]);
+
+        let bar: Box<dyn Base> = Box::new(Bar(String::from("bar")));
+        let mut bar_clone = bar.clone_box().unwrap();
+        bar_clone.downcast_mut::<Bar>().unwrap().0.push('!');
+        assert_eq!(bar.downcast_ref::<Bar>().unwrap().0, "bar");
+        assert_eq!(bar_clone.downcast_ref::<Bar>().unwrap().0, "bar!");
+
+        // Types missing from the list can't be cloned.
+        let baz: Box<dyn Base> = Box::new(Baz);
+        assert!(baz.clone_box().is_none());
+    }
+
+    #[test]
+    #[allow(dead_code)]
+    fn clone_box_with_where_clause() {
+        use super::Downcast;
+
+        trait Base<T: Copy>: Downcast {}
+        impl_downcast!(Base<T> where T: Copy clone => [Foo]);
+        #[derive(Clone)]
+        struct Foo(u32);
+        impl<T: Copy> Base<T> for Foo {}
+
+        let foo: Box<dyn Base<u8>> = Box::new(Foo(7));
+        assert_eq!(foo.clone_box().unwrap().downcast_ref::<Foo>().unwrap().0, 7);
+    }
+
+    #[test]
+    #[allow(dead_code)]
+    fn from_any() {
+        use super::Downcast;
+        use super::__std::any::Any;
+
+        trait Base: Downcast {}
+        impl_downcast!(Base from_any => [Foo, Bar]);
+        struct Foo(u32);
+        impl Base for Foo {}
+        struct Bar;
+        impl Base for Bar {}
+        struct Baz;
+        impl Base for Baz {}
+
+        let any: Box<dyn Any> = Box::new(Foo(42));
+        let base = <dyn Base>::from_any(any).map_err(|_| "Shouldn't happen.").unwrap();
+        assert_eq!(base.downcast_ref::<Foo>().unwrap().0, 42);
+        let any: Box<dyn Any> = Box::new(Bar);
+        assert!(<dyn Base>::from_any(any).map_err(|_| "Shouldn't happen.").unwrap().is::<Bar>());
+
+        // Unregistered types are handed back untouched.
+        let any: Box<dyn Any> = Box::new(Baz);
+        let any = <dyn Base>::from_any(any).map(|_| ()).unwrap_err();
+        assert!(any.is::<Baz>());
+        let any: Box<dyn Any> = Box::new(42u32);
+        assert!(<dyn Base>::from_any(any).is_err());
+    }
+
+    #[test]
+    #[allow(dead_code)]
+    fn find_downcast_ref_in_cow() {
+        use super::__alloc::borrow::Cow;
+        use super::{find_downcast_ref, Downcast};
+
+        trait Base: Downcast {}
+        impl_downcast!(Base clone => [Foo, Bar]);
+        impl Clone for Box<dyn Base> {
+            fn clone(&self) -> Self { self.clone_box().unwrap() }
+        }
+        #[derive(Clone)]
+        struct Foo(u32);
+        impl Base for Foo {}
+        #[derive(Clone)]
+        struct Bar;
+        impl Base for Bar {}
+
+        let items: Vec<Box<dyn Base>> = vec![Box::new(Bar), Box::new(Foo(1)), Box::new(Foo(2))];
+        let found: Option<&Foo> = find_downcast_ref(&items);
+        assert_eq!(found.unwrap().0, 1);
+        let found: Option<&Foo> = find_downcast_ref(&items[2..]);
+        assert_eq!(found.unwrap().0, 2);
+
+        let borrowed: Cow<[Box<dyn Base>]> = Cow::Borrowed(&items);
+        let found: Option<&Foo> = find_downcast_ref(&borrowed);
+        assert_eq!(found.unwrap().0, 1);
+        let owned: Cow<[Box<dyn Base>]> = Cow::Owned(borrowed[1..].to_vec());
+        let found: Option<&Foo> = find_downcast_ref(&owned);
+        assert_eq!(found.unwrap().0, 1);
+        let found: Option<&Bar> = find_downcast_ref(&owned);
+        assert!(found.is_none());
+    }
+
+    #[test]
+    #[allow(dead_code)]
+    fn collect_downcast() {
+        use super::{collect_downcast, Downcast};
+        use super::__alloc::collections::VecDeque;
+
+        trait Base: Downcast {}
+        impl_downcast!(Base);
+        struct Foo(u32);
+        impl Base for Foo {}
+        struct Bar(u32);
+        impl Base for Bar {}
+
+        let items: Vec<Box<dyn Base>> = vec![Box::new(Foo(1)), Box::new(Foo(2))];
+        let foos: Vec<Box<Foo>> = collect_downcast(items).map_err(|_| "Shouldn't happen.").unwrap();
+        assert_eq!(foos.iter().map(|foo| foo.0).collect::<Vec<_>>(), [1, 2]);
+
+        let items: Vec<Box<dyn Base>> = Vec::new();
+        let foos: VecDeque<Box<Foo>> = collect_downcast(items).map_err(|_| "Shouldn't happen.").unwrap();
+        assert!(foos.is_empty());
+
+        let items: Vec<Box<dyn Base>> =
+            vec![Box::new(Foo(1)), Box::new(Bar(2)), Box::new(Foo(3)), Box::new(Bar(4))];
+        let bad = collect_downcast::<Foo, _, Vec<_>, _>(items).map(|_| ()).unwrap_err();
+        assert_eq!(bad.downcast_ref::<Bar>().unwrap().0, 2);
+    }
+
+    #[test]
+    #[allow(dead_code)]
+    fn downcast_vec_indexed() {
+        use super::{downcast_vec_indexed, Downcast};
+
+        trait Base: Downcast {}
+        impl_downcast!(Base);
+        struct Foo(u32);
+        impl Base for Foo {}
+        struct Bar(u32);
+        impl Base for Bar {}
+
+        let items: Vec<Box<dyn Base>> = vec![Box::new(Foo(1)), Box::new(Foo(2))];
+        let foos = downcast_vec_indexed::<Foo, _>(items).map_err(|_| "Shouldn't happen.").unwrap();
+        assert_eq!(foos.iter().map(|foo| foo.0).collect::<Vec<_>>(), [1, 2]);
+
+        let items: Vec<Box<dyn Base>> =
+            vec![Box::new(Foo(1)), Box::new(Foo(2)), Box::new(Bar(3)), Box::new(Bar(4))];
+        let (index, items) = downcast_vec_indexed::<Foo, _>(items).map(|_| ()).unwrap_err();
+        assert_eq!(index, 2);
+        assert_eq!(items.len(), 4);
+        assert_eq!(items[2].downcast_ref::<Bar>().unwrap().0, 3);
+        let (index, _) = downcast_vec_indexed::<Bar, _>(items).map(|_| ()).unwrap_err();
+        assert_eq!(index, 0);
+    }
+
+    #[test]
+    #[allow(dead_code)]
+    fn split_downcast() {
+        use super::{split_downcast, Downcast};
+
+        trait Base: Downcast { fn id(&self) -> u32; }
+        impl_downcast!(Base);
+        struct Foo(u32);
+        impl Base for Foo { fn id(&self) -> u32 { self.0 } }
+        struct Bar(u32);
+        impl Base for Bar { fn id(&self) -> u32 { self.0 } }
+
+        let items: Vec<Box<dyn Base>> =
+            vec![Box::new(Bar(1)), Box::new(Foo(2)), Box::new(Bar(3)), Box::new(Foo(4)), Box::new(Foo(5))];
+        // Any iterator will do, not just a `Vec`.
+        let (foos, others) = split_downcast::<Foo, _, _>(items.into_iter().filter(|item| item.id() != 5));
+        assert_eq!(foos.iter().map(|foo| foo.0).collect::<Vec<_>>(), [2, 4]);
+        assert_eq!(others.iter().map(|other| other.id()).collect::<Vec<_>>(), [1, 3]);
+        assert_eq!(foos.len() + others.len(), 4);
+
+        let (foos, others) = split_downcast::<Foo, dyn Base, _>(Vec::new());
+        assert!(foos.is_empty() && others.is_empty());
+    }
+
+    #[test]
+    #[allow(dead_code)]
+    fn homogeneous_type_id() {
+        use super::{homogeneous_type_id, Downcast};
+        use super::__std::any::TypeId;
+
+        trait Base: Downcast {}
+        impl_downcast!(Base);
+        struct Foo(u32);
+        impl Base for Foo {}
+        struct Bar;
+        impl Base for Bar {}
+
+        let items: Vec<Box<dyn Base>> = vec![Box::new(Foo(1)), Box::new(Foo(2)), Box::new(Foo(3))];
+        assert_eq!(homogeneous_type_id(&items), Some(TypeId::of::<Foo>()));
+        let items: Vec<Box<dyn Base>> = vec![Box::new(Bar)];
+        assert_eq!(homogeneous_type_id(&items), Some(TypeId::of::<Bar>()));
+        let items: Vec<Box<dyn Base>> = vec![Box::new(Foo(1)), Box::new(Bar), Box::new(Foo(3))];
+        assert_eq!(homogeneous_type_id(&items), None);
+        let items: Vec<Box<dyn Base>> = Vec::new();
+        assert_eq!(homogeneous_type_id(&items), None);
+    }
+
+    #[test]
+    #[allow(dead_code)]
+    fn remove_first_of_type() {
+        use super::{remove_first_of_type, Downcast};
+
+        trait Base: Downcast { fn id(&self) -> u32; }
+        impl_downcast!(Base);
+        struct Foo(u32);
+        impl Base for Foo { fn id(&self) -> u32 { self.0 } }
+        struct Bar(u32);
+        impl Base for Bar { fn id(&self) -> u32 { self.0 } }
+        struct Baz;
+        impl Base for Baz { fn id(&self) -> u32 { 0 } }
+
+        let mut items: Vec<Box<dyn Base>> =
+            vec![Box::new(Bar(1)), Box::new(Foo(2)), Box::new(Bar(3)), Box::new(Foo(4))];
+        assert_eq!(remove_first_of_type::<Foo, _>(&mut items).map(|foo| foo.0), Some(2));
+        assert_eq!(items.iter().map(|item| item.id()).collect::<Vec<_>>(), [1, 3, 4]);
+        assert_eq!(remove_first_of_type::<Foo, _>(&mut items).map(|foo| foo.0), Some(4));
+        assert!(remove_first_of_type::<Foo, _>(&mut items).is_none());
+        assert!(remove_first_of_type::<Baz, _>(&mut items).is_none());
+        assert_eq!(items.iter().map(|item| item.id()).collect::<Vec<_>>(), [1, 3]);
+
+        let mut items: Vec<Box<dyn Base>> = Vec::new();
+        assert!(remove_first_of_type::<Bar, _>(&mut items).is_none());
+    }
+
+    #[test]
+    #[allow(dead_code)]
+    fn upgrade_downcast() {
+        use super::{upgrade_downcast, Downcast};
+        use super::__alloc::rc::Rc;
+
+        trait Base: Downcast {}
+        impl_downcast!(Base);
+        struct Foo(u32);
+        impl Base for Foo {}
+        struct Bar;
+        impl Base for Bar {}
+
+        let base: Rc<dyn Base> = Rc::new(Foo(1));
+        let weak = Rc::downgrade(&base);
+        let foo = upgrade_downcast::<Foo, _>(&weak).unwrap();
+        assert_eq!(foo.0, 1);
+        assert_eq!(Rc::strong_count(&foo), 2);
+        assert!(upgrade_downcast::<Bar, _>(&weak).is_none());
+        assert_eq!(Rc::strong_count(&foo), 2);
+
+        drop(base);
+        drop(foo);
+        assert!(upgrade_downcast::<Foo, _>(&weak).is_none());
+    }
+
+    #[test]
+    #[allow(dead_code)]
+    fn downcast_ref_iter_keeps_positions() {
+        use super::{downcast_ref_iter, Downcast};
+
+        trait Base: Downcast {}
+        impl_downcast!(Base);
+        struct Foo(u32);
+        impl Base for Foo {}
+        struct Bar;
+        impl Base for Bar {}
+
+        let items: Vec<Box<dyn Base>> =
+            vec![Box::new(Bar), Box::new(Foo(1)), Box::new(Bar), Box::new(Foo(2))];
+        let foos: Vec<Option<u32>> = downcast_ref_iter::<Foo, _>(&items).map(|foo| foo.map(|foo| foo.0)).collect();
+        assert_eq!(foos, [None, Some(1), None, Some(2)]);
+        let bars: Vec<bool> = downcast_ref_iter::<Bar, _>(&items).map(|bar| bar.is_some()).collect();
+        assert_eq!(bars, [true, false, true, false]);
+        assert_eq!(downcast_ref_iter::<Foo, _>(&items[..0]).count(), 0);
+    }
+
+    #[test]
+    #[allow(dead_code)]
+    fn single_into_inner() {
+        use super::{Downcast, SingleImpl};
+
+        trait Base: Downcast + SingleImpl<Foo> {}
+        impl_downcast!(single Base => Foo);
+        struct Foo(u32);
+        impl Base for Foo {}
+
+        let base: Box<dyn Base> = Box::new(Foo(42));
+        let foo: Box<Foo> = base.into_inner();
+        assert_eq!(foo.0, 42);
+    }
+
+    #[test]
+    #[allow(dead_code)]
+    fn downcast_selected() {
+        use super::Downcast;
+
+        trait Base: Downcast {}
+        impl_downcast!(Base selector => [Foo, Bar]);
+        trait Other<T>: Downcast {}
+        impl_downcast!(Other<T> selector OtherSelector => [Foo]);
+        struct Foo(u32);
+        impl Base for Foo {}
+        impl<T> Other<T> for Foo {}
+        struct Bar;
+        impl Base for Bar {}
+
+        let foo: Box<dyn Base> = Box::new(Foo(42));
+        let any = foo.downcast_selected(TargetSelector::Foo).unwrap();
+        assert_eq!(any.downcast_ref::<Foo>().unwrap().0, 42);
+        assert!(foo.downcast_selected(TargetSelector::Bar).is_none());
+
+        let bar: Box<dyn Base> = Box::new(Bar);
+        assert!(bar.downcast_selected(TargetSelector::Bar).unwrap().is::<Bar>());
+        assert!(bar.downcast_selected(TargetSelector::Foo).is_none());
+
+        let other: Box<dyn Other<u8>> = Box::new(Foo(7));
+        assert!(other.downcast_selected(OtherSelector::Foo).is_some());
+    }
+
+    #[test]
+    #[allow(dead_code)]
+    fn common_header() {
+        use super::Downcast;
+
+        #[derive(Debug, PartialEq)]
+        struct Header { id: u32 }
+        trait Base: Downcast {}
+        impl_downcast!(Base header Header => [(Foo, |foo| &foo.header), (Bar, |bar| &bar.1)]);
+        trait Other<T>: Downcast {}
+        impl_downcast!(Other<T> where T: Copy header Header => [(Foo, |foo| &foo.header),]);
+        struct Foo { header: Header, value: f64 }
+        impl Base for Foo {}
+        impl<T: Copy> Other<T> for Foo {}
+        struct Bar(u8, Header);
+        impl Base for Bar {}
+        struct Baz;
+        impl Base for Baz {}
+
+        let foo: Box<dyn Base> = Box::new(Foo { header: Header { id: 1 }, value: 0.5 });
+        assert_eq!(foo.common_header(), Some(&Header { id: 1 }));
+        let bar: Box<dyn Base> = Box::new(Bar(0, Header { id: 2 }));
+        assert_eq!(bar.common_header(), Some(&Header { id: 2 }));
+        let baz: Box<dyn Base> = Box::new(Baz);
+        assert_eq!(baz.common_header(), None);
+
+        let other: Box<dyn Other<u8>> = Box::new(Foo { header: Header { id: 3 }, value: 0.5 });
+        assert_eq!(other.common_header().unwrap().id, 3);
+    }
+
+    #[test]
+    #[allow(dead_code)]
+    #[cfg_attr(debug_assertions, should_panic(expected = "found `"))]
+    fn assert_concrete_mismatch() {
+        use super::Downcast;
+
+        trait Base: Downcast {}
+        impl_downcast!(Base extras => [assert_concrete]);
+        struct Foo;
+        impl Base for Foo {}
+        struct Bar;
+        impl Base for Bar {}
+
+        let base: Box<dyn Base> = Box::new(Bar);
+        base.assert_concrete::<Bar>();
+        // Panics in debug builds only; a no-op in release builds.
+        base.assert_concrete::<Foo>();
+    }
+
+    #[test]
+    #[allow(dead_code)]
+    fn kind() {
+        use super::Downcast;
+
+        trait Base: Downcast {}
+        impl_downcast!(Base kind BaseKind => [Foo, Bar]);
+        trait Other<T>: Downcast {}
+        impl_downcast!(Other<T> kind => [Foo]);
+        struct Foo;
+        impl Base for Foo {}
+        impl<T> Other<T> for Foo {}
+        struct Bar;
+        impl Base for Bar {}
+        struct Baz;
+        impl Base for Baz {}
+        impl<T> Other<T> for Baz {}
+
+        let objs: [Box<dyn Base>; 3] = [Box::new(Foo), Box::new(Bar), Box::new(Baz)];
+        let kinds: Vec<BaseKind> = objs.iter().map(|obj| obj.kind()).collect();
+        assert_eq!(kinds, [BaseKind::Foo, BaseKind::Bar, BaseKind::Unknown]);
+
+        let other: Box<dyn Other<u8>> = Box::new(Foo);
+        assert_eq!(other.kind(), TypeKind::Foo);
+        let other: Box<dyn Other<u8>> = Box::new(Baz);
+        assert_eq!(other.kind(), TypeKind::Unknown);
+    }
+
+    #[test]
+    #[allow(dead_code)]
+    fn kind_non_exhaustive() {
+        use super::Downcast;
+
+        trait Base: Downcast {}
+        impl_downcast!(Base kind non_exhaustive => [Foo]);
+        trait Other<T>: Downcast {}
+        impl_downcast!(Other<T> where T: Copy kind non_exhaustive OtherKind => [Foo, Bar]);
+        struct Foo;
+        impl Base for Foo {}
+        impl<T: Copy> Other<T> for Foo {}
+        struct Bar;
+        impl Base for Bar {}
+        impl<T: Copy> Other<T> for Bar {}
+
+        let foo: Box<dyn Base> = Box::new(Foo);
+        assert_eq!(foo.kind(), TypeKind::Foo);
+        // Bar was added as an implementor after the list was written.
+        let bar: Box<dyn Base> = Box::new(Bar);
+        let name = match bar.kind() {
+            TypeKind::Foo => "foo",
+            TypeKind::Unknown => "unknown",
+            _ => unreachable!(),
         };
-        (
-            $test_mod_name:ident,
-            trait $base_trait:path { $($base_impl:tt)* },
-            non_sync: { $($non_sync_def:tt)+ },
-            sync: { $($sync_def:tt)+ }
-        ) => {
-            test_mod! {
-                $test_mod_name,
-                trait $base_trait { $($base_impl:tt)* },
-                type $base_trait,
-                non_sync: { $($non_sync_def)* },
-                sync: { $($sync_def)* }
+        assert_eq!(name, "unknown");
+
+        let bar: Box<dyn Other<u8>> = Box::new(Bar);
+        assert_eq!(bar.kind(), OtherKind::Bar);
+    }
+
+    #[test]
+    #[allow(dead_code)]
+    fn downcast_union() {
+        use super::Downcast;
+
+        trait Base: Downcast { fn name(&self) -> &'static str; }
+        impl_downcast!(Base ref_enum BaseRef => [Foo, Bar]);
+        trait Other<T>: Downcast {}
+        impl_downcast!(Other<T> where T: Copy ref_enum OtherRef => [Foo]);
+        struct Foo(u32);
+        impl Base for Foo { fn name(&self) -> &'static str { "foo" } }
+        impl<T: Copy> Other<T> for Foo {}
+        struct Bar(bool);
+        impl Base for Bar { fn name(&self) -> &'static str { "bar" } }
+        struct Baz;
+        impl Base for Baz { fn name(&self) -> &'static str { "baz" } }
+        impl<T: Copy> Other<T> for Baz {}
+
+        fn describe(base: &dyn Base) -> (&'static str, u32) {
+            match base.downcast_union() {
+                BaseRef::Foo(foo) => ("Foo", foo.0),
+                BaseRef::Bar(bar) => ("Bar", bar.0 as u32),
+                BaseRef::Other(other) => (other.name(), 0),
             }
-        };
+        }
+        assert_eq!(describe(&Foo(42)), ("Foo", 42));
+        assert_eq!(describe(&Bar(true)), ("Bar", 1));
+        assert_eq!(describe(&Baz), ("baz", 0));
+
+        let other: Box<dyn Other<u8>> = Box::new(Foo(7));
+        match other.downcast_union() {
+            OtherRef::Foo(foo) => assert_eq!(foo.0, 7),
+            OtherRef::Other(_) => panic!("expected a Foo"),
+        }
+        let other: Box<dyn Other<u8>> = Box::new(Baz);
+        let union = other.downcast_union();
+        let copy = union;
+        assert!(match (union, copy) { (OtherRef::Other(obj), OtherRef::Other(_)) => obj.is::<Baz>(), _ => false });
+    }
+
+    #[test]
+    #[allow(dead_code)]
+    fn nth_type_ref() {
+        use super::Downcast;
+
+        trait Base: Downcast {}
+        impl_downcast!(Base order => [Foo, Bar, Baz]);
+        struct Foo;
+        impl Base for Foo {}
+        struct Bar(u32);
+        impl Base for Bar {}
+        struct Baz;
+        impl Base for Baz {}
+        struct Qux;
+        impl Base for Qux {}
+
+        let index_of = |obj: &dyn Base| (0..4).find(|&n| obj.nth_type_ref(n).is_some());
+        let objs: [Box<dyn Base>; 4] = [Box::new(Baz), Box::new(Foo), Box::new(Qux), Box::new(Bar(7))];
+        let indices: Vec<_> = objs.iter().map(|obj| index_of(&**obj)).collect();
+        assert_eq!(indices, [Some(2), Some(0), None, Some(1)]);
+        assert_eq!(objs[3].nth_type_ref(1).and_then(|any| any.downcast_ref::<Bar>()).map(|bar| bar.0), Some(7));
+        assert!(objs[0].nth_type_ref(3).is_none());
+    }
+
+    #[test]
+    #[allow(dead_code)]
+    fn type_predicates() {
+        use super::Downcast;
+
+        trait Base: Downcast {}
+        impl_downcast!(Base predicates => [Foo, Bar]);
+        trait Other<T>: Downcast {}
+        impl_downcast!(Other<T> where T: Copy predicates => [Bar]);
+        struct Foo;
+        impl Base for Foo {}
+        struct Bar;
+        impl Base for Bar {}
+        impl<T: Copy> Other<T> for Bar {}
+        struct Baz;
+        impl Base for Baz {}
+
+        let preds = <dyn Base>::type_predicates();
+        assert_eq!(preds.len(), 2);
+        let objs: [Box<dyn Base>; 3] = [Box::new(Foo), Box::new(Bar), Box::new(Baz)];
+        let matches: Vec<[bool; 2]> = objs.iter().map(|obj| [preds[0](&**obj), preds[1](&**obj)]).collect();
+        assert_eq!(matches, [[true, false], [false, true], [false, false]]);
+
+        let other: Box<dyn Other<u8>> = Box::new(Bar);
+        assert!(<dyn Other<u8>>::type_predicates()[0](&*other));
+    }
+
+    #[test]
+    fn type_id_matches_any() {
+        use super::__std::any::{Any, TypeId};
+
+        assert_eq!(super::__type_id::<u32>(), TypeId::of::<u32>());
+        assert_eq!(super::__type_id::<dyn Any>(), TypeId::of::<dyn Any>());
+        assert!(super::__type_id::<u32>() != TypeId::of::<i32>());
+    }
+
+    #[test]
+    #[allow(dead_code)]
+    fn downcast_rc_keeps_allocation() {
+        use super::Downcast;
+        use __alloc::rc::Rc;
+
+        trait Base: Downcast {}
+        impl_downcast!(Base);
+        struct Foo(u32);
+        impl Base for Foo {}
+
+        // The very same `Rc` comes back with the strong count unchanged.
+        let rc: Rc<dyn Base> = Rc::new(Foo(42));
+        let shared = rc.clone();
+        let foo = rc.downcast_rc::<Foo>().map_err(|_| "Shouldn't happen.").unwrap();
+        assert_eq!(&*foo as *const Foo as *const (), &*shared as *const dyn Base as *const ());
+        assert_eq!(Rc::strong_count(&foo), 2);
+    }
+
+    #[test]
+    #[allow(dead_code)]
+    fn upcast() {
+        use super::{Downcast, Upcast};
+
+        trait Base<T>: Downcast where T: Clone {}
+        impl_downcast!(Base<T> where T: Clone);
+        struct Foo(u32);
+        impl Base<u8> for Foo {}
+
+        let base = <dyn Base<u8> as Upcast<Foo>>::upcast(Box::new(Foo(42)));
+        assert!(base.is::<Foo>());
+    }
+
+    /// Exercises the methods opted into with `extras => [...]` on a few shapes of traits. The
+    /// registry-backed `has_capability` and `concrete_type_code` are tested in `tests/`.
+    mod extras {
+        #[cfg(not(feature = "std"))]
+        use __alloc::boxed::Box;
+
+        macro_rules! test_extras {
+            (
+                $test_name:ident,
+                impl $base_trait:path { $($base_impl:tt)* },
+                type $base_type:ty,
+                { $($def:tt)+ },
+                [ $($more_tests:block)* ]
+            ) => {
+                #[test]
+                #[allow(dead_code)]
+                fn $test_name() {
+                    #[allow(unused_imports)]
+                    use super::super::{Downcast, DowncastOutcome, DowncastSync};
+                    use super::super::__alloc::rc::Rc;
+                    use super::super::__std::{alloc::Layout, any::{type_name, TypeId}};
+
+                    $($def)+
+
+                    #[derive(Clone, Debug)]
+                    struct Foo(u32);
+                    impl $base_trait for Foo { $($base_impl)* }
+                    #[derive(Clone, Debug)]
+                    struct Bar(f64);
+                    impl $base_trait for Bar { $($base_impl)* }
+
+                    let mut base: Box<$base_type> = Box::new(Foo(42));
+                    assert_eq!(base.type_name(), type_name::<Foo>());
+                    assert_eq!(base.concrete_layout(), Layout::new::<Foo>());
+                    assert_eq!(base.concrete_type_id(), TypeId::of::<Foo>());
+                    assert_eq!(<$base_type>::downcast_ref_of::<Foo>(&*base).map(|foo| foo.0), Some(42));
+                    assert_eq!(base.project(|obj| obj.downcast_ref::<Foo>().map(|foo| &foo.0)), Some(&42));
+                    assert_eq!(base.project(|obj| obj.downcast_ref::<Bar>().map(|bar| &bar.0)), None);
+                    assert_eq!(base.descendants_of_type::<Foo, _>(|_| &[]).count(), 0);
+                    base.assert_concrete::<Foo>();
+
+                    // Downcast along with the address of the object.
+                    let (foo, addr) = base.downcast_ref_addr::<Foo>().unwrap();
+                    assert_eq!(foo as *const Foo as usize, addr);
+                    assert_eq!(&*base as *const $base_type as *const u8 as usize, addr);
+                    assert!(base.downcast_ref_addr::<Bar>().is_none());
+
+                    // Downcast, then convert back into the very same trait object.
+                    let (foo, upcast) = base.downcast_ref_reupcast::<Foo>().unwrap();
+                    assert_eq!(foo.0, 42);
+                    assert_eq!(
+                        upcast(foo) as *const $base_type as *const u8,
+                        &*base as *const $base_type as *const u8);
+                    assert!(upcast(foo).is::<Foo>());
+                    assert!(base.downcast_ref_reupcast::<Bar>().is_none());
+
+                    // Fallible downcasts report both type names.
+                    assert_eq!(base.downcast_ref_named::<Foo>().map(|foo| foo.0), Ok(42));
+                    assert_eq!(
+                        base.downcast_ref_named::<Bar>().map(|_| ()),
+                        Err((type_name::<Bar>(), type_name::<Foo>())));
+                    assert_eq!(base.try_downcast_ref::<Foo>().map(|foo| foo.0).ok(), Some(42));
+                    let err = base.try_downcast_ref::<Bar>().unwrap_err();
+                    assert_eq!(err.expected_type_name(), type_name::<Bar>());
+                    assert_eq!(err.actual_type_name(), type_name::<Foo>());
+                    assert!(base.try_downcast_mut::<Bar>().is_err());
+                    base.try_downcast_mut::<Foo>().unwrap().0 = 43;
+                    let err = base.try_downcast::<Bar>().unwrap_err();
+                    assert_eq!(err.actual_type_name(), type_name::<Foo>());
+                    let base = err.into_inner();
+
+                    // Transform the object only if it is a Foo.
+                    let base = base.map_downcast(|bar: Box<Bar>| -> Box<Bar> { unreachable!("{:?}", bar) });
+                    let base = base.map_downcast(|mut foo: Box<Foo>| { foo.0 -= 1; foo });
+                    assert_eq!(base.downcast_ref::<Foo>().map(|foo| foo.0), Some(42));
+
+                    // Move the object out only if it is a Foo, keeping the box otherwise.
+                    let base = base.downcast_inline::<Bar>().map(|_| ()).unwrap_err();
+                    let base = match base.downcast_owned_or_keep::<Bar>() {
+                        DowncastOutcome::Original(base) => base,
+                        DowncastOutcome::Concrete(_) => panic!("Shouldn't happen."),
+                    };
+                    match base.downcast_owned_or_keep::<Foo>() {
+                        DowncastOutcome::Concrete(foo) => assert_eq!(42, foo.0),
+                        DowncastOutcome::Original(_) => panic!("Shouldn't happen."),
+                    }
+                    let base: Box<$base_type> = Box::new(Foo(42));
+                    assert_eq!(42, base.downcast_inline::<Foo>().map_err(|_| "Shouldn't happen.").unwrap().0);
+
+                    // Tag the object with its concrete type.
+                    let base: Box<$base_type> = Box::new(Foo(42));
+                    let msg = base.tag();
+                    assert!(msg.is::<Foo>() && !msg.is::<Bar>());
+                    assert_eq!(42, msg.downcast::<Foo>().map_err(|_| "Shouldn't happen.").unwrap().0);
+
+                    // Clone the shared object into a uniquely owned Rc<Foo>.
+                    let rc: Rc<$base_type> = Rc::new(Foo(42));
+                    let shared = rc.clone();
+                    let foo = rc.downcast_rc_or_clone::<Foo>().map_err(|_| "Shouldn't happen.").unwrap();
+                    assert_eq!((foo.0, Rc::strong_count(&foo), Rc::strong_count(&shared)), (42, 1, 1));
+
+                    // Downcast weak references to live and dropped objects.
+                    let weak = Rc::downgrade(&shared);
+                    let weak = <$base_type>::downcast_weak::<Bar>(weak).map(|_| ()).unwrap_err();
+                    let weak_foo = <$base_type>::downcast_weak::<Foo>(weak.clone())
+                        .map_err(|_| "Shouldn't happen.").unwrap();
+                    assert_eq!(weak_foo.upgrade().unwrap().0, 42);
+                    assert_eq!((Rc::strong_count(&shared), Rc::weak_count(&shared)), (1, 2));
+                    drop(shared);
+                    assert!(<$base_type>::downcast_weak::<Foo>(weak).is_err());
+                    assert!(weak_foo.upgrade().is_none());
+
+                    $($more_tests)*
+                }
+            };
+        }
+
+        test_extras!(non_generic, impl Base {}, type dyn Base, {
+            trait Base: Downcast {}
+            impl_downcast!(Base extras => [
+                type_name, concrete_layout, concrete_type_id, downcast_inline, map_downcast,
+                downcast_rc_or_clone, downcast_weak, downcast_ref_of, try_downcast,
+                try_downcast_ref, try_downcast_mut, project, tag, descendants_of_type,
+                downcast_ref_addr, downcast_ref_reupcast, downcast_owned_or_keep, assert_concrete,
+                downcast_ref_named,
+            ]);
+        }, []);
+
+        test_extras!(generic_associated, impl Base<u32> { type H = u8; }, type dyn Base<u32, H = u8>, {
+            trait Base<T>: Downcast where T: Copy { type H; }
+            impl_downcast!(Base<T> assoc H where T: Copy, H: Copy extras => [
+                type_name, concrete_layout, concrete_type_id, downcast_inline, map_downcast,
+                downcast_rc_or_clone, downcast_weak, downcast_ref_of, try_downcast,
+                try_downcast_ref, try_downcast_mut, project, tag, descendants_of_type,
+                downcast_ref_addr, downcast_ref_reupcast, downcast_owned_or_keep, assert_concrete,
+                downcast_ref_named,
+            ]);
+        }, []);
+
+        test_extras!(concrete, impl Base<u32> {}, type dyn Base<u32>, {
+            trait Base<T>: Downcast {}
+            impl_downcast!(concrete Base<u32> extras => [
+                type_name, concrete_layout, concrete_type_id, downcast_inline, map_downcast,
+                downcast_rc_or_clone, downcast_weak, downcast_ref_of, try_downcast,
+                try_downcast_ref, try_downcast_mut, project, tag, descendants_of_type,
+                downcast_ref_addr, downcast_ref_reupcast, downcast_owned_or_keep, assert_concrete,
+                downcast_ref_named,
+            ]);
+        }, []);
+
+        test_extras!(sync, impl Base {}, type dyn Base, {
+            trait Base: DowncastSync {}
+            impl_downcast!(sync Base extras => [
+                type_name, concrete_layout, concrete_type_id, downcast_inline, map_downcast,
+                downcast_rc_or_clone, downcast_weak, downcast_weak_arc,
+                downcast_ref_of, try_downcast, try_downcast_ref, try_downcast_mut, project, tag,
+                descendants_of_type, downcast_ref_addr, downcast_ref_reupcast,
+                downcast_owned_or_keep, assert_concrete, downcast_ref_named,
+            ]);
+        }, [{
+            use super::super::__alloc::sync::Arc;
 
+            // Downcast weak references to live and dropped objects.
+            let arc: Arc<dyn Base> = Arc::new(Foo(42));
+            let weak = Arc::downgrade(&arc);
+            let weak = <dyn Base>::downcast_weak_arc::<Bar>(weak).map(|_| ()).unwrap_err();
+            let weak_foo = <dyn Base>::downcast_weak_arc::<Foo>(weak.clone())
+                .map_err(|_| "Shouldn't happen.").unwrap();
+            assert_eq!(weak_foo.upgrade().unwrap().0, 42);
+            assert_eq!(Arc::strong_count(&arc), 1);
+            drop(arc);
+            assert!(<dyn Base>::downcast_weak_arc::<Foo>(weak).is_err());
+        }]);
     }
 
     test_mod!(non_generic, trait Base {},
@@ -593,6 +4897,18 @@ mod test {
             impl_downcast!(sync Base);
         });
 
+    // Behaves the same without `#[inline]` in debug builds and with it in release builds, which CI
+    // tests as well.
+    test_mod!(non_generic_no_inline, trait Base {},
+        non_sync: {
+            trait Base: Downcast {}
+            impl_downcast!(Base no_inline);
+        },
+        sync: {
+            trait Base: DowncastSync {}
+            impl_downcast!(sync Base no_inline);
+        });
+
     test_mod!(generic, trait Base<u32> {},
         non_sync: {
             trait Base<T>: Downcast {}
@@ -613,6 +4929,16 @@ mod test {
             impl_downcast!(sync Base<T> where T: Copy);
         });
 
+    test_mod!(inline_constrained_generic, trait Base<u32, u8> {},
+        non_sync: {
+            trait Base<T: Copy, U: Clone + Into<::__std::option::Option<u8>>>: Downcast {}
+            impl_downcast!(Base<T: Copy, U: Clone + Into<::__std::option::Option<u8>>>);
+        },
+        sync: {
+            trait Base<T: Copy, U: Clone + Into<::__std::option::Option<u8>>>: DowncastSync {}
+            impl_downcast!(sync Base<T: Copy, U: Clone + Into<::__std::option::Option<u8>>>);
+        });
+
     test_mod!(associated,
         trait Base { type H = f32; },
         type dyn Base<H=f32>,
@@ -637,6 +4963,18 @@ mod test {
             impl_downcast!(sync Base assoc H where H: Copy);
         });
 
+    test_mod!(inline_constrained_associated,
+        trait Base { type H = f32; },
+        type dyn Base<H=f32>,
+        non_sync: {
+            trait Base: Downcast { type H: Copy; }
+            impl_downcast!(Base assoc H: Copy);
+        },
+        sync: {
+            trait Base: DowncastSync { type H: Copy; }
+            impl_downcast!(sync Base assoc H: Copy);
+        });
+
     test_mod!(param_and_associated,
         trait Base<u32> { type H = f32; },
         type dyn Base<u32, H=f32>,
@@ -661,6 +4999,100 @@ mod test {
             impl_downcast!(sync Base<T> assoc H where T: Clone, H: Copy);
         });
 
+    test_mod!(inline_constrained_param_and_associated,
+        trait Base<u32, u64> { type H = f32; },
+        type dyn Base<u32, u64, H=f32>,
+        non_sync: {
+            trait Base<T: Into<U>, U>: Downcast { type H: Copy; }
+            impl_downcast!(Base<T: Into<U>, U> assoc H where H: Copy);
+        },
+        sync: {
+            trait Base<T: Into<U>, U>: DowncastSync { type H: Copy; }
+            impl_downcast!(sync Base<T: Into<U>, U> assoc H where H: Copy);
+        });
+
+    test_mod!(inline_constrained_params_and_associated,
+        trait Base<u32> { type G = u8; type H = f32; },
+        type dyn Base<u32, G=u8, H=f32>,
+        non_sync: {
+            trait Base<T: Clone>: Downcast { type G: Into<f64>; type H: Copy + PartialOrd; }
+            impl_downcast!(Base<T: Clone> assoc G: Into<f64>, H: Copy + PartialOrd<H> where T: Copy);
+        },
+        sync: {
+            trait Base<T: Clone>: DowncastSync { type G: Into<f64>; type H: Copy + PartialOrd; }
+            impl_downcast!(sync Base<T: Clone> assoc G: Into<f64>, H: Copy + PartialOrd<H>, where T: Copy);
+        });
+
+    test_mod!(lifetime_param, trait Base<'static> {},
+        type dyn Base<'static>,
+        non_sync: {
+            trait Base<'a>: Downcast {}
+            impl_downcast!(Base<'a>);
+        },
+        sync: {
+            trait Base<'a>: DowncastSync {}
+            impl_downcast!(sync Base<'a>);
+        });
+
+    test_mod!(lifetime_param_associated,
+        trait Base<'static> { type H = f32; },
+        type dyn Base<'static, H=f32>,
+        non_sync: {
+            trait Base<'a>: Downcast { type H: Copy; }
+            impl_downcast!(Base<'a> assoc H where H: Copy);
+        },
+        sync: {
+            trait Base<'a>: DowncastSync { type H: Copy; }
+            impl_downcast!(sync Base<'a> assoc H where H: Copy);
+        });
+
+    test_mod!(lifetime_param_associated_constrained,
+        trait Base<'static, u32> { type H = f32; },
+        type dyn Base<'static, u32, H=f32>,
+        non_sync: {
+            trait Base<'a, T: Clone>: Downcast { type H: Copy; }
+            impl_downcast!(Base<'a, T> assoc H where T: Clone, H: Copy);
+        },
+        sync: {
+            trait Base<'a, T: Clone>: DowncastSync { type H: Copy; }
+            impl_downcast!(sync Base<'a, T> assoc H where T: Clone, H: Copy);
+        });
+
+    #[cfg(feature = "const-generics")] // Requires Rust 1.51.
+    test_mod!(const_param, trait Base<4> {},
+        non_sync: {
+            trait Base<const N: usize>: Downcast {}
+            impl_downcast!(Base<const N: usize>);
+        },
+        sync: {
+            trait Base<const N: usize>: DowncastSync {}
+            impl_downcast!(sync Base<const N: usize>);
+        });
+
+    #[cfg(feature = "const-generics")] // Requires Rust 1.51.
+    test_mod!(const_and_type_param_associated,
+        trait Base<u32, 4> { type H = f32; },
+        type dyn Base<u32, 4, H=f32>,
+        non_sync: {
+            trait Base<T: Clone, const N: usize>: Downcast { type H: Copy; }
+            impl_downcast!(Base<T, const N: usize> assoc H where T: Clone, H: Copy);
+        },
+        sync: {
+            trait Base<T: Clone, const N: usize>: DowncastSync { type H: Copy; }
+            impl_downcast!(sync Base<T, const N: usize> assoc H where T: Clone, H: Copy);
+        });
+
+    #[cfg(feature = "const-generics")] // Requires Rust 1.51.
+    test_mod!(concrete_const_param, trait Base<u32, 4> {},
+        non_sync: {
+            trait Base<T, const N: usize>: Downcast {}
+            impl_downcast!(concrete Base<u32, 4>);
+        },
+        sync: {
+            trait Base<T, const N: usize>: DowncastSync {}
+            impl_downcast!(sync concrete Base<u32, { 2 + 2 }>);
+        });
+
     test_mod!(concrete_parametrized, trait Base<u32> {},
         non_sync: {
             trait Base<T>: Downcast {}
@@ -676,11 +5108,11 @@ mod test {
         type dyn Base<H=u32>,
         non_sync: {
             trait Base: Downcast { type H; }
-            impl_downcast!(concrete Base assoc H=u32);
+            impl_downcast!(concrete Base assoc H=u32,);
         },
         sync: {
             trait Base: DowncastSync { type H; }
-            impl_downcast!(sync concrete Base assoc H=u32);
+            impl_downcast!(sync concrete Base assoc H=u32,);
         });
 
     test_mod!(concrete_parametrized_associated,
@@ -688,10 +5120,10 @@ mod test {
         type dyn Base<u32, H=f32>,
         non_sync: {
             trait Base<T>: Downcast { type H; }
-            impl_downcast!(concrete Base<u32> assoc H=f32);
+            impl_downcast!(concrete Base<u32> assoc H=f32,);
         },
         sync: {
             trait Base<T>: DowncastSync { type H; }
-            impl_downcast!(sync concrete Base<u32> assoc H=f32);
+            impl_downcast!(sync concrete Base<u32> assoc H=f32,);
         });
 }
@@ -0,0 +1,43 @@
+#[macro_use]
+extern crate downcast_rs;
+
+use std::alloc::{alloc, Layout};
+use downcast_rs::Downcast;
+
+trait Base: Downcast {}
+impl_downcast!(Base);
+
+#[derive(Debug)]
+struct Foo(u32);
+impl Base for Foo {}
+#[derive(Debug)]
+struct Bar;
+impl Base for Bar {}
+
+#[test]
+#[allow(unsafe_code)]
+fn downcast_box_from_raw() {
+    // Allocate and initialize a `Foo` by hand rather than through `Box::new`.
+    let ptr = unsafe { alloc(Layout::new::<Foo>()) } as *mut Foo;
+    assert!(!ptr.is_null());
+    unsafe { ptr.write(Foo(42)) };
+    let base: Box<dyn Base> = unsafe { Box::from_raw(ptr) };
+
+    let base = base.downcast::<Bar>().unwrap_err();
+    let foo = base.downcast::<Foo>().map_err(|_| "Shouldn't happen.").unwrap();
+    assert_eq!(&*foo as *const Foo, ptr as *const Foo);
+    assert_eq!(foo.0, 42);
+}
+
+#[test]
+fn downcast_err_keeps_object() {
+    struct Named(String);
+    impl Base for Named {}
+
+    let base: Box<dyn Base> = Box::new(Named("named".to_string()));
+    let addr = &*base as *const dyn Base as *const u8;
+    let base = base.downcast::<Foo>().unwrap_err();
+    assert_eq!(&*base as *const dyn Base as *const u8, addr);
+    let named = base.downcast::<Named>().map_err(|_| "Shouldn't happen.").unwrap();
+    assert_eq!(named.0, "named");
+}
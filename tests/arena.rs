@@ -0,0 +1,50 @@
+#[macro_use]
+extern crate downcast_rs;
+
+use downcast_rs::{try_mut, try_ref, Downcast};
+
+trait Base: Downcast {}
+impl_downcast!(Base);
+
+struct Foo(u32);
+impl Base for Foo {}
+struct Bar;
+impl Base for Bar {}
+
+// Stands in for an arena allocator handing out references that live as long as the arena.
+struct Arena {
+    objs: Vec<Box<dyn Base>>,
+}
+
+impl Arena {
+    fn get(&self, i: usize) -> &dyn Base { &*self.objs[i] }
+    fn get_mut(&mut self, i: usize) -> &mut dyn Base { &mut *self.objs[i] }
+}
+
+fn foo_in(arena: &Arena, i: usize) -> Option<&Foo> {
+    let obj = arena.get(i);
+    try_ref(obj)
+}
+
+fn foo_in_mut(arena: &mut Arena, i: usize) -> Option<&mut Foo> {
+    let obj = arena.get_mut(i);
+    try_mut(obj)
+}
+
+#[test]
+fn arena_ref() {
+    let arena = Arena { objs: vec![Box::new(Foo(1)), Box::new(Bar)] };
+    let foos: Vec<&Foo> = (0..2).filter_map(|i| foo_in(&arena, i)).collect();
+    assert_eq!(foos.len(), 1);
+    assert_eq!(foos[0].0, 1);
+    assert!(try_ref::<_, Bar>(arena.get(1)).is_some());
+}
+
+#[test]
+fn arena_mut() {
+    let mut arena = Arena { objs: vec![Box::new(Bar), Box::new(Foo(1))] };
+    assert!(foo_in_mut(&mut arena, 0).is_none());
+    let foo = foo_in_mut(&mut arena, 1).unwrap();
+    foo.0 += 1;
+    assert_eq!(foo_in(&arena, 1).unwrap().0, 2);
+}
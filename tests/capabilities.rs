@@ -0,0 +1,43 @@
+#[macro_use]
+extern crate downcast_rs;
+
+use downcast_rs::{register_capability, Downcast};
+
+trait Widget: Downcast {}
+impl_downcast!(Widget extras => [has_capability]);
+
+#[derive(PartialEq)]
+enum Capability {
+    Render,
+    Focus,
+}
+
+// Another capability type that must not be confused with `Capability`.
+#[derive(PartialEq)]
+struct Level(u8);
+
+struct Button;
+impl Widget for Button {}
+struct Label;
+impl Widget for Label {}
+struct Spacer;
+impl Widget for Spacer {}
+
+#[test]
+fn registered_capabilities() {
+    register_capability::<Button, _>(Capability::Render);
+    register_capability::<Button, _>(Capability::Focus);
+    register_capability::<Button, _>(Capability::Focus);
+    register_capability::<Label, _>(Capability::Render);
+    register_capability::<Label, _>(Level(1));
+
+    let widgets: Vec<Box<dyn Widget>> = vec![Box::new(Button), Box::new(Label), Box::new(Spacer)];
+    let render: Vec<bool> = widgets.iter().map(|w| w.has_capability(Capability::Render)).collect();
+    assert_eq!(render, [true, true, false]);
+    let focus: Vec<bool> = widgets.iter().map(|w| w.has_capability(Capability::Focus)).collect();
+    assert_eq!(focus, [true, false, false]);
+
+    assert!(widgets[1].has_capability(Level(1)));
+    assert!(!widgets[1].has_capability(Level(2)));
+    assert!(!widgets[0].has_capability(Level(1)));
+}
@@ -0,0 +1,42 @@
+// Checked by `cargo clippy --all-targets`: the generated impls must not trip these lints.
+#![deny(clippy::multiple_bound_locations)]
+
+#[macro_use]
+extern crate downcast_rs;
+use downcast_rs::Downcast;
+
+#[allow(dead_code)]
+trait Base<'a, T: Copy>: Downcast {
+    type H: Clone;
+}
+impl_downcast!(Base<'a, T> assoc H where T: Copy, H: Clone);
+
+#[allow(dead_code)]
+trait ConcreteBase<T: Copy>: Downcast {}
+impl_downcast!(concrete ConcreteBase<u32>);
+
+// Bounds given inline are only written once.
+#[allow(dead_code)]
+trait InlineBase<T: Copy, U: Clone + Into<Vec<u8>>>: Downcast {
+    type H: Clone;
+}
+impl_downcast!(InlineBase<T: Copy, U: Clone + Into<Vec<u8>>> assoc H: Clone);
+
+struct Foo;
+impl<'a> Base<'a, u8> for Foo {
+    type H = u16;
+}
+impl ConcreteBase<u32> for Foo {}
+impl InlineBase<u8, String> for Foo {
+    type H = u16;
+}
+
+#[test]
+fn multiple_bound_locations() {
+    let base: Box<dyn Base<'static, u8, H = u16>> = Box::new(Foo);
+    assert!(base.is::<Foo>());
+    let base: Box<dyn ConcreteBase<u32>> = Box::new(Foo);
+    assert!(base.is::<Foo>());
+    let base: Box<dyn InlineBase<u8, String, H = u16>> = Box::new(Foo);
+    assert!(base.is::<Foo>());
+}
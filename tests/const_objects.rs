@@ -0,0 +1,31 @@
+// Trait objects can be created in const contexts, but are only downcast at runtime; see the
+// `compile_fail` tests in the crate for the const case.
+#[macro_use]
+extern crate downcast_rs;
+use downcast_rs::Downcast;
+
+trait Base: Downcast {}
+impl_downcast!(Base);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Foo(u32);
+impl Base for Foo {}
+struct Bar;
+impl Base for Bar {}
+
+const FOO: Foo = Foo(7);
+const CONST_OBJ: &dyn Base = &Foo(1);
+static STATIC_OBJ: &(dyn Base + Sync) = &Bar;
+
+#[test]
+fn downcast_objects_from_const_contexts() {
+    assert!(CONST_OBJ.is::<Foo>());
+    assert_eq!(CONST_OBJ.downcast_ref::<Foo>(), Some(&Foo(1)));
+    let static_obj: &dyn Base = STATIC_OBJ;
+    assert!(static_obj.is::<Bar>());
+    assert!(static_obj.downcast_ref::<Foo>().is_none());
+
+    // The baseline: a box coerced at runtime from a constant value.
+    let boxed: Box<dyn Base> = Box::new(FOO);
+    assert_eq!(boxed.downcast::<Foo>().map_err(|_| "Shouldn't happen.").unwrap(), Box::new(FOO));
+}
@@ -0,0 +1,62 @@
+#[macro_use]
+extern crate downcast_rs;
+use downcast_rs::Downcast;
+
+trait Node: Downcast {
+    fn children(&self) -> &[Box<dyn Node>] { &[] }
+}
+impl_downcast!(Node extras => [descendants_of_type]);
+
+struct Group(&'static str, Vec<Box<dyn Node>>);
+impl Node for Group {
+    fn children(&self) -> &[Box<dyn Node>] { &self.1 }
+}
+struct Mesh(u32);
+impl Node for Mesh {}
+struct Light;
+impl Node for Light {}
+
+fn group(name: &'static str, children: Vec<Box<dyn Node>>) -> Box<dyn Node> {
+    Box::new(Group(name, children))
+}
+
+fn scene() -> Box<dyn Node> {
+    group("root", vec![
+        Box::new(Mesh(1)),
+        group("a", vec![
+            Box::new(Light),
+            group("a1", vec![Box::new(Mesh(2)), Box::new(Mesh(3))]),
+            Box::new(Mesh(4)),
+        ]),
+        group("b", vec![]),
+        Box::new(Mesh(5)),
+        group("c", vec![Box::new(Mesh(6))]),
+    ])
+}
+
+#[test]
+fn descendants_depth_first() {
+    let root = scene();
+    let meshes: Vec<u32> = root.descendants_of_type::<Mesh, _>(|node| node.children())
+        .map(|mesh| mesh.0)
+        .collect();
+    assert_eq!(meshes, [1, 2, 3, 4, 5, 6]);
+
+    // Groups precede their own descendants, and the root isn't visited.
+    let groups: Vec<&str> = root.descendants_of_type::<Group, _>(|node| node.children())
+        .map(|group| group.0)
+        .collect();
+    assert_eq!(groups, ["a", "a1", "b", "c"]);
+}
+
+#[test]
+fn descendants_lazy_and_empty() {
+    let root = scene();
+    let mut meshes = root.descendants_of_type::<Mesh, _>(|node| node.children());
+    assert_eq!(meshes.next().map(|mesh| mesh.0), Some(1));
+    assert_eq!(meshes.next().map(|mesh| mesh.0), Some(2));
+
+    assert_eq!(root.descendants_of_type::<Light, _>(|node| node.children()).count(), 1);
+    let leaf: Box<dyn Node> = Box::new(Mesh(7));
+    assert_eq!(leaf.descendants_of_type::<Mesh, _>(|node| node.children()).count(), 0);
+}
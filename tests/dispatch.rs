@@ -0,0 +1,43 @@
+#[macro_use]
+extern crate downcast_rs;
+use downcast_rs::Downcast;
+
+trait Base: Downcast {}
+impl_downcast!(Base);
+
+struct Foo(u32);
+impl Base for Foo {}
+struct Bar(String);
+impl Base for Bar {}
+struct Baz;
+impl Base for Baz {}
+
+fn describe(base: &dyn Base) -> String {
+    dispatch_downcast!(base, {
+        f: Foo => format!("foo {}", f.0),
+        b: Bar => format!("bar {}", b.0),
+        Baz => "baz".to_string(),
+        _ => "other".to_string(),
+    })
+}
+
+struct Qux;
+impl Base for Qux {}
+
+#[test]
+fn dispatch_arms() {
+    assert_eq!(describe(&Foo(1)), "foo 1");
+    assert_eq!(describe(&Bar("x".to_string())), "bar x");
+    assert_eq!(describe(&Baz), "baz");
+    assert_eq!(describe(&Qux), "other");
+}
+
+#[test]
+fn dispatch_boxed() {
+    let items: Vec<Box<dyn Base>> = vec![Box::new(Bar("y".to_string())), Box::new(Qux), Box::new(Foo(2))];
+    // `item` is a `&Box<dyn Base>`, so it is dereferenced to the `Box` first.
+    let sum: u32 = items.iter().map(|item| dispatch_downcast!(*item, { f: Foo => f.0, _ => 10 })).sum();
+    assert_eq!(sum, 22);
+    let only_fallback = dispatch_downcast!(items[0], { Bar => 5, _ => 0 });
+    assert_eq!(only_fallback, 5);
+}
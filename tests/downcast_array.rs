@@ -0,0 +1,28 @@
+#[macro_use]
+extern crate downcast_rs;
+use downcast_rs::{downcast_array_ref, Downcast};
+
+trait Base: Downcast {}
+impl_downcast!(Base);
+
+#[derive(Debug, PartialEq)]
+struct Foo(u32);
+impl Base for Foo {}
+struct Bar;
+impl Base for Bar {}
+
+#[test]
+fn downcast_array_ref_homogeneous() {
+    let arr: [Box<dyn Base>; 3] = [Box::new(Foo(1)), Box::new(Foo(2)), Box::new(Foo(3))];
+    let foos: [&Foo; 3] = downcast_array_ref(&arr).unwrap();
+    assert_eq!(foos, [&Foo(1), &Foo(2), &Foo(3)]);
+    let empty: [Box<dyn Base>; 0] = [];
+    assert_eq!(downcast_array_ref::<Foo, _, 0>(&empty), Some([]));
+}
+
+#[test]
+fn downcast_array_ref_mismatch() {
+    let arr: [Box<dyn Base>; 3] = [Box::new(Foo(1)), Box::new(Bar), Box::new(Foo(3))];
+    assert!(downcast_array_ref::<Foo, _, 3>(&arr).is_none());
+    assert!(downcast_array_ref::<Bar, _, 3>(&arr).is_none());
+}
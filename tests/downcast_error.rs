@@ -0,0 +1,49 @@
+#[macro_use]
+extern crate downcast_rs;
+
+use downcast_rs::Downcast;
+
+trait Base: Downcast {}
+impl_downcast!(Base extras => [try_downcast, try_downcast_ref, try_downcast_mut]);
+
+#[derive(Debug)]
+struct Foo(u32);
+impl Base for Foo {}
+#[derive(Debug)]
+struct Bar;
+impl Base for Bar {}
+
+#[test]
+fn try_downcast_display() {
+    let base: Box<dyn Base> = Box::new(Foo(42));
+    let err = base.try_downcast::<Bar>().unwrap_err();
+    let msg = err.to_string();
+    assert!(msg.contains(err.expected_type_name()) && msg.contains("::Bar"));
+    assert!(msg.contains(err.actual_type_name()) && msg.contains("::Foo"));
+    let dbg = format!("{:?}", err);
+    assert!(dbg.contains("::Bar") && dbg.contains("::Foo"));
+    // The original box is handed back intact.
+    assert_eq!(err.into_inner().downcast_ref::<Foo>().unwrap().0, 42);
+}
+
+#[test]
+fn try_downcast_ref_mut_display() {
+    let mut base: Box<dyn Base> = Box::new(Bar);
+    let msg = base.try_downcast_ref::<Foo>().unwrap_err().to_string();
+    assert!(msg.contains("::Foo") && msg.contains("::Bar"));
+    let msg = base.try_downcast_mut::<Foo>().unwrap_err().to_string();
+    assert!(msg.contains("::Foo") && msg.contains("::Bar"));
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn try_downcast_question_mark() {
+    use std::error::Error;
+
+    fn get(base: &dyn Base) -> Result<u32, Box<dyn Error>> {
+        Ok(base.try_downcast_ref::<Foo>()?.0)
+    }
+    assert_eq!(get(&Foo(42)).unwrap(), 42);
+    let msg = get(&Bar).unwrap_err().to_string();
+    assert!(msg.contains("::Foo") && msg.contains("::Bar"));
+}
@@ -0,0 +1,96 @@
+#[macro_use]
+extern crate downcast_rs;
+
+use downcast_rs::{Downcast, DowncastMap};
+
+trait Component: Downcast {
+    fn name(&self) -> &'static str;
+}
+impl_downcast!(Component extras => [concrete_type_id]);
+
+#[derive(Debug, Default, PartialEq)]
+struct Position(i32, i32);
+impl Component for Position {
+    fn name(&self) -> &'static str { "position" }
+}
+#[derive(Debug, PartialEq)]
+struct Health(u32);
+impl Component for Health {
+    fn name(&self) -> &'static str { "health" }
+}
+
+#[test]
+fn insert_get_remove() {
+    let mut map: DowncastMap<dyn Component> = DowncastMap::new();
+    assert!(map.is_empty());
+    assert_eq!(map.insert(Position(1, 2)), None);
+    assert_eq!(map.insert(Health(10)), None);
+    assert_eq!(map.insert(Position(3, 4)), Some(Position(1, 2)));
+    assert_eq!(map.len(), 2);
+
+    assert_eq!(map.get::<Position>(), Some(&Position(3, 4)));
+    map.get_mut::<Health>().unwrap().0 -= 1;
+    assert_eq!(map.get::<Health>(), Some(&Health(9)));
+    assert!(map.get::<u32>().is_none());
+
+    let mut names: Vec<_> = map.values().map(|component| component.name()).collect();
+    names.sort();
+    assert_eq!(names, ["health", "position"]);
+
+    assert_eq!(map.remove::<Health>(), Some(Health(9)));
+    assert_eq!(map.remove::<Health>(), None);
+    assert!(!map.contains::<Health>());
+    assert!(map.contains::<Position>());
+}
+
+#[test]
+fn get_or_insert_with() {
+    let mut map: DowncastMap<dyn Component> = DowncastMap::default();
+    let mut calls = 0;
+    map.get_or_insert_with(|| { calls += 1; Health(5) }).0 += 1;
+    map.get_or_insert_with(|| { calls += 1; Health(100) }).0 += 1;
+    assert_eq!(calls, 1);
+    assert_eq!(map.get::<Health>(), Some(&Health(7)));
+}
+
+#[test]
+fn entry() {
+    let mut map: DowncastMap<dyn Component> = DowncastMap::new();
+    assert!(!map.entry::<Position>().is_occupied());
+    *map.entry::<Position>().or_default() = Position(1, 1);
+    assert!(map.entry::<Position>().is_occupied());
+
+    map.entry::<Position>().and_modify(|pos| pos.0 += 1).or_insert(Position(0, 0));
+    assert_eq!(map.get::<Position>(), Some(&Position(2, 1)));
+
+    let health = map.entry::<Health>().and_modify(|health| health.0 = 0).or_insert(Health(3));
+    health.0 *= 2;
+    assert_eq!(map.get::<Health>(), Some(&Health(6)));
+    assert_eq!(map.entry::<Health>().or_insert_with(|| Health(0)).0, 6);
+}
+
+#[test]
+fn ordered_iter() {
+    use std::any::TypeId;
+
+    let mut map: DowncastMap<dyn Component> = DowncastMap::new();
+    map.insert(Health(10));
+    map.insert(Position(1, 2));
+    assert_eq!(map.get::<Health>(), Some(&Health(10)));
+
+    let ids: Vec<TypeId> = map.iter().map(|(id, _)| id).collect();
+    let mut sorted = vec![TypeId::of::<Health>(), TypeId::of::<Position>()];
+    sorted.sort();
+    assert_eq!(ids, sorted);
+    for (id, component) in map.iter() {
+        assert_eq!(id, component.concrete_type_id());
+    }
+
+    // The order doesn't depend on the order of insertion.
+    let mut other: DowncastMap<dyn Component> = DowncastMap::new();
+    other.insert(Position(1, 2));
+    other.insert(Health(10));
+    let names: Vec<_> = map.values().map(|component| component.name()).collect();
+    let other_names: Vec<_> = other.values().map(|component| component.name()).collect();
+    assert_eq!(names, other_names);
+}
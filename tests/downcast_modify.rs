@@ -0,0 +1,45 @@
+#[macro_use]
+extern crate downcast_rs;
+
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use downcast_rs::{downcast_modify, Downcast};
+
+trait Base: Downcast {}
+impl_downcast!(Base);
+
+// Deliberately neither `Default` nor `Copy`.
+#[derive(Debug)]
+struct Foo(String);
+impl Base for Foo {}
+#[derive(Debug)]
+struct Bar(Vec<u32>);
+impl Base for Bar {}
+
+#[test]
+fn downcast_modify_match() {
+    let mut base: Box<dyn Base> = Box::new(Foo("foo".to_string()));
+    let addr = base.downcast_ref::<Foo>().unwrap() as *const Foo;
+    assert!(downcast_modify(&mut base, || Foo(String::new()), |Foo(s): Foo| Foo(s + "bar")));
+    let foo = base.downcast_ref::<Foo>().unwrap();
+    assert_eq!(foo.0, "foobar");
+    assert_eq!(foo as *const Foo, addr);
+}
+
+#[test]
+fn downcast_modify_mismatch() {
+    let mut base: Box<dyn Base> = Box::new(Bar(vec![1, 2]));
+    let placeholder = || -> Foo { unreachable!() };
+    assert!(!downcast_modify(&mut base, placeholder, |_: Foo| -> Foo { unreachable!() }));
+    assert_eq!(base.downcast_ref::<Bar>().unwrap().0, [1, 2]);
+}
+
+#[test]
+fn downcast_modify_panic_leaves_placeholder() {
+    let mut base: Box<dyn Base> = Box::new(Foo("foo".to_string()));
+    let payload = catch_unwind(AssertUnwindSafe(|| {
+        downcast_modify(&mut base, || Foo("placeholder".to_string()), |_: Foo| -> Foo { panic!("boom") })
+    }))
+    .unwrap_err();
+    assert_eq!(payload.downcast_ref::<&str>(), Some(&"boom"));
+    assert_eq!(base.downcast_ref::<Foo>().unwrap().0, "placeholder");
+}
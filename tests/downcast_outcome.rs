@@ -0,0 +1,34 @@
+#[macro_use]
+extern crate downcast_rs;
+
+use downcast_rs::{Downcast, DowncastOutcome};
+
+trait Base: Downcast {}
+impl_downcast!(Base extras => [downcast_owned_or_keep]);
+
+struct Foo(u32);
+impl Base for Foo {}
+struct Bar;
+impl Base for Bar {}
+
+#[test]
+fn downcast_owned_or_keep_concrete() {
+    let base: Box<dyn Base> = Box::new(Foo(42));
+    match base.downcast_owned_or_keep::<Foo>() {
+        DowncastOutcome::Concrete(foo) => assert_eq!(foo.0, 42),
+        DowncastOutcome::Original(_) => panic!("expected a `Foo`"),
+    }
+}
+
+#[test]
+fn downcast_owned_or_keep_original() {
+    let base: Box<dyn Base> = Box::new(Bar);
+    let addr = &*base as *const dyn Base as *const u8;
+    match base.downcast_owned_or_keep::<Foo>() {
+        DowncastOutcome::Concrete(_) => panic!("expected a `Bar`"),
+        DowncastOutcome::Original(base) => {
+            assert!(base.is::<Bar>());
+            assert_eq!(&*base as *const dyn Base as *const u8, addr);
+        }
+    }
+}
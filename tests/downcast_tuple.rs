@@ -0,0 +1,45 @@
+#[macro_use]
+extern crate downcast_rs;
+use downcast_rs::Downcast;
+
+trait Base: Downcast {}
+impl_downcast!(Base);
+
+#[derive(Debug, PartialEq)]
+struct Foo(u32);
+impl Base for Foo {}
+#[derive(Debug, PartialEq)]
+struct Bar(bool);
+impl Base for Bar {}
+#[derive(Debug, PartialEq)]
+struct Baz;
+impl Base for Baz {}
+
+fn layout() -> (Box<dyn Base>, Box<dyn Base>, Box<dyn Base>) {
+    (Box::new(Foo(1)), Box::new(Bar(true)), Box::new(Baz))
+}
+
+#[test]
+fn all_match() {
+    let (a, b, c) = layout();
+    let objs: (&Foo, &Bar, &Baz) = downcast_tuple!((a, b, c) as (Foo, Bar, Baz)).unwrap();
+    assert_eq!(objs, (&Foo(1), &Bar(true), &Baz));
+    // Trailing commas and single positions.
+    assert_eq!(downcast_tuple!((a, b,) as (Foo, Bar,)), Some((&Foo(1), &Bar(true))));
+    assert_eq!(downcast_tuple!((c) as (Baz)), Some((&Baz,)));
+}
+
+#[test]
+fn single_mismatch() {
+    let layout = layout();
+    assert!(downcast_tuple!((layout.0, layout.1, layout.2) as (Foo, Bar, Foo)).is_none());
+    assert!(downcast_tuple!((layout.0, layout.1, layout.2) as (Foo, Baz, Baz)).is_none());
+    assert!(downcast_tuple!((layout.0, layout.1, layout.2) as (Bar, Bar, Baz)).is_none());
+}
+
+#[test]
+fn through_references() {
+    let objs: Vec<Box<dyn Base>> = vec![Box::new(Bar(false)), Box::new(Foo(2))];
+    let (first, second) = (&objs[0], &objs[1]);
+    assert_eq!(downcast_tuple!((*first, *second) as (Bar, Foo)), Some((&Bar(false), &Foo(2))));
+}
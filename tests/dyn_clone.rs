@@ -0,0 +1,52 @@
+#[macro_use]
+extern crate downcast_rs;
+
+use downcast_rs::Downcast;
+
+trait Base: Downcast + BaseClone {}
+impl_downcast!(Base clone => BaseClone);
+
+#[derive(Clone, Debug, PartialEq)]
+struct Foo(String);
+impl Base for Foo {}
+#[derive(Clone, Debug, PartialEq)]
+struct Bar;
+impl Base for Bar {}
+
+trait Generic<T>: Downcast + GenericClone<T> {}
+impl_downcast!(Generic<T> clone => GenericClone);
+impl Generic<u32> for Foo {}
+
+trait Borrowing<'a>: Downcast + BorrowingClone<'a> {}
+impl_downcast!(Borrowing<'a> clone => BorrowingClone);
+impl Borrowing<'static> for Bar {}
+
+trait Concrete<T>: Downcast + ConcreteClone {}
+impl_downcast!(concrete Concrete<u32> clone => ConcreteClone);
+impl Concrete<u32> for Bar {}
+
+#[test]
+fn clone_box() {
+    let foo: Box<dyn Base> = Box::new(Foo("foo".to_string()));
+    let clone = foo.clone_box();
+    assert_ne!(&*clone as *const dyn Base as *const u8, &*foo as *const dyn Base as *const u8);
+    assert_eq!(clone.downcast_ref::<Foo>(), foo.downcast_ref::<Foo>());
+
+    let bar: Box<dyn Base> = Box::new(Bar);
+    assert_eq!(bar.clone_box().downcast_ref::<Bar>(), Some(&Bar));
+}
+
+#[test]
+fn clone_box_generic() {
+    let foo: Box<dyn Generic<u32>> = Box::new(Foo("foo".to_string()));
+    drop(foo.clone_box());
+    assert_eq!(foo.clone_box().downcast::<Foo>().map_err(|_| "Shouldn't happen.").unwrap().0, "foo");
+}
+
+#[test]
+fn clone_box_lifetime_and_concrete() {
+    let bar: Box<dyn Borrowing<'static>> = Box::new(Bar);
+    assert!(bar.clone_box().is::<Bar>());
+    let bar: Box<dyn Concrete<u32>> = Box::new(Bar);
+    assert!(bar.clone_box().is::<Bar>());
+}
@@ -0,0 +1,38 @@
+#![deny(bare_trait_objects)]
+
+#[macro_use]
+extern crate downcast_rs;
+
+use downcast_rs::Downcast;
+
+trait Base: Downcast {}
+impl_downcast!(dyn Base);
+
+trait Generic<T: Copy>: Downcast { type H; }
+impl_downcast!(dyn Generic<T> assoc H where T: Copy);
+
+trait Concrete<T>: Downcast {}
+impl_downcast!(concrete dyn Concrete<u32>);
+
+#[derive(Debug)]
+struct Foo(u32);
+impl Base for Foo {}
+impl Generic<u8> for Foo { type H = (); }
+impl Concrete<u32> for Foo {}
+struct Bar;
+impl Base for Bar {}
+impl Generic<u8> for Bar { type H = (); }
+impl Concrete<u32> for Bar {}
+
+#[test]
+fn dyn_trait_input() {
+    let base: Box<dyn Base> = Box::new(Foo(42));
+    assert!(!base.is::<Bar>());
+    assert_eq!(base.downcast::<Foo>().map_err(|_| "Shouldn't happen.").unwrap().0, 42);
+
+    let generic: Box<dyn Generic<u8, H = ()>> = Box::new(Bar);
+    assert!(generic.downcast_ref::<Bar>().is_some());
+
+    let concrete: Box<dyn Concrete<u32>> = Box::new(Foo(7));
+    assert_eq!(concrete.downcast_ref::<Foo>().map(|foo| foo.0), Some(7));
+}
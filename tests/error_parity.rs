@@ -0,0 +1,83 @@
+// Mirrors the downcasting tests of `std::error::Error` to check that `dyn Error` code carries
+// over to `dyn Base` unchanged.
+
+#[macro_use]
+extern crate downcast_rs;
+
+use std::error::Error;
+use std::fmt;
+use downcast_rs::Downcast;
+
+trait Base: Downcast + fmt::Debug {}
+impl_downcast!(Base);
+
+#[derive(Debug, PartialEq)]
+struct A;
+#[derive(Debug, PartialEq)]
+struct B;
+
+impl fmt::Display for A {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { write!(f, "A") }
+}
+impl fmt::Display for B {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { write!(f, "B") }
+}
+impl Error for A {}
+impl Error for B {}
+impl Base for A {}
+impl Base for B {}
+
+#[test]
+fn downcasting_error() {
+    let mut a = A;
+    let a = &mut a as &mut (dyn Error + 'static);
+    assert_eq!(a.downcast_ref::<A>(), Some(&A));
+    assert_eq!(a.downcast_ref::<B>(), None);
+    assert_eq!(a.downcast_mut::<A>(), Some(&mut A));
+    assert_eq!(a.downcast_mut::<B>(), None);
+
+    let a: Box<dyn Error> = Box::new(A);
+    match a.downcast::<B>() {
+        Ok(..) => panic!("expected error"),
+        Err(e) => assert_eq!(*e.downcast::<A>().unwrap(), A),
+    }
+}
+
+#[test]
+fn downcasting_base() {
+    let mut a = A;
+    let a = &mut a as &mut dyn Base;
+    assert_eq!(a.downcast_ref::<A>(), Some(&A));
+    assert_eq!(a.downcast_ref::<B>(), None);
+    assert_eq!(a.downcast_mut::<A>(), Some(&mut A));
+    assert_eq!(a.downcast_mut::<B>(), None);
+
+    let a: Box<dyn Base> = Box::new(A);
+    match a.downcast::<B>() {
+        Ok(..) => panic!("expected error"),
+        Err(e) => assert_eq!(*e.downcast::<A>().unwrap(), A),
+    }
+}
+
+#[test]
+fn is_matches_error() {
+    let a: Box<dyn Error> = Box::new(A);
+    let base: Box<dyn Base> = Box::new(A);
+    assert_eq!(a.is::<A>(), base.is::<A>());
+    assert_eq!(a.is::<B>(), base.is::<B>());
+}
+
+type BoxDowncast<B> = fn(Box<B>) -> Result<Box<A>, Box<B>>;
+
+#[test]
+fn downcast_signatures_match_error() {
+    // The failed `downcast` hands back the original box with the same type as `Error::downcast`.
+    let error: BoxDowncast<dyn Error> = |e| e.downcast::<A>();
+    let base: BoxDowncast<dyn Base> = |b| b.downcast::<A>();
+    assert!(error(Box::new(B)).unwrap_err().is::<B>());
+    assert!(base(Box::new(B)).unwrap_err().is::<B>());
+
+    let error_ref: for<'a> fn(&'a (dyn Error + 'static)) -> Option<&'a A> = |e| e.downcast_ref::<A>();
+    let base_ref: for<'a> fn(&'a dyn Base) -> Option<&'a A> = |b| b.downcast_ref::<A>();
+    assert_eq!(error_ref(&A), base_ref(&A));
+}
@@ -0,0 +1,49 @@
+// Downcasting is gated behind `sync`, which is on by default and off with `--no-default-features`.
+
+#[macro_use]
+extern crate downcast_rs;
+
+use downcast_rs::Downcast;
+
+trait Base: Downcast {
+    fn id(&self) -> u32;
+}
+impl_downcast!(Base feature = "sync" kind => [Foo, Bar]);
+
+struct Foo;
+impl Base for Foo {
+    fn id(&self) -> u32 { 1 }
+}
+struct Bar;
+impl Base for Bar {
+    fn id(&self) -> u32 { 2 }
+}
+
+#[test]
+#[cfg(feature = "sync")]
+fn feature_on() {
+    let base: Box<dyn Base> = Box::new(Foo);
+    assert_eq!(base.id(), 1);
+    assert!(base.is::<Foo>());
+    assert_eq!(base.kind(), TypeKind::Foo);
+    assert!(base.downcast::<Bar>().is_err());
+}
+
+// Neither these nor `TypeKind` would compile if `impl_downcast!` had generated its own.
+#[cfg(not(feature = "sync"))]
+impl dyn Base {
+    fn is<T: Base>(&self) -> bool {
+        Downcast::as_any(self).is::<T>()
+    }
+}
+#[cfg(not(feature = "sync"))]
+struct TypeKind;
+
+#[test]
+#[cfg(not(feature = "sync"))]
+fn feature_off() {
+    let _ = TypeKind;
+    let base: Box<dyn Base> = Box::new(Bar);
+    assert_eq!(base.id(), 2);
+    assert!(base.is::<Bar>() && !base.is::<Foo>());
+}
@@ -0,0 +1,41 @@
+#[macro_use]
+extern crate downcast_rs;
+
+use std::rc::Rc;
+use downcast_rs::Downcast;
+
+trait Base: Downcast {}
+impl_downcast!(Base);
+
+#[derive(Debug, PartialEq)]
+struct Foo(u32);
+impl Base for Foo {}
+#[derive(Debug, PartialEq)]
+struct Bar;
+impl Base for Bar {}
+
+struct Wrapper {
+    inner: Box<dyn Base>,
+}
+impl_field_downcast!(Wrapper, inner: Base);
+
+struct Tagged<T> {
+    tag: T,
+    shared: Rc<dyn Base>,
+}
+impl_field_downcast!(impl<T> Tagged<T>, shared: Base);
+
+#[test]
+fn wrapper_field() {
+    let wrapper = Wrapper { inner: Box::new(Foo(42)) };
+    assert_eq!(wrapper.downcast_ref::<Foo>(), Some(&Foo(42)));
+    assert_eq!(wrapper.downcast_ref::<Bar>(), None);
+}
+
+#[test]
+fn generic_wrapper_field() {
+    let tagged = Tagged { tag: "bar", shared: Rc::new(Bar) };
+    assert_eq!(tagged.downcast_ref::<Bar>(), Some(&Bar));
+    assert_eq!(tagged.downcast_ref::<Foo>(), None);
+    assert_eq!(tagged.tag, "bar");
+}
@@ -0,0 +1,61 @@
+// The code generated by `impl_downcast!` by default, and that expanded from `dispatch_downcast!`,
+// must be usable from crates forbidding unsafe code.
+#![forbid(unsafe_code)]
+
+#[macro_use]
+extern crate downcast_rs;
+use downcast_rs::Downcast;
+
+use std::rc::Rc;
+
+trait Base: Downcast {}
+impl_downcast!(Base clone => [Foo] from_any => [Foo] selector => [Foo, Bar] doc_hidden extras => [try_downcast_ref]);
+
+trait Generic<'a, T: Copy>: Downcast {
+    type H;
+}
+impl_downcast!(Generic<'a, T> assoc H where T: Copy);
+
+#[derive(Clone, Debug)]
+struct Foo(u32);
+impl Base for Foo {}
+impl<'a, T: Copy> Generic<'a, T> for Foo {
+    type H = ();
+}
+#[derive(Debug)]
+struct Bar;
+impl Base for Bar {}
+
+#[test]
+fn safe_subset() {
+    let mut base: Box<dyn Base> = Box::new(Foo(1));
+    assert!(base.is::<Foo>());
+    base.downcast_mut::<Foo>().unwrap().0 = 2;
+    assert_eq!(base.downcast_ref::<Foo>().unwrap().0, 2);
+    assert!(base.try_downcast_ref::<Bar>().is_err());
+    assert!(base.clone_box().is_some());
+    assert!(base.downcast_selected(TargetSelector::Foo).is_some());
+    let foo = base.downcast::<Foo>().map_err(|_| "Shouldn't happen.").unwrap();
+    assert_eq!(foo.0, 2);
+
+    let rc: Rc<dyn Base> = Rc::new(Foo(3));
+    assert_eq!(rc.downcast_rc::<Foo>().map_err(|_| "Shouldn't happen.").unwrap().0, 3);
+    let any: Box<dyn std::any::Any> = Box::new(Foo(5));
+    assert!(<dyn Base>::from_any(any).is_ok());
+
+    let generic: Box<dyn Generic<'static, u8, H = ()>> = Box::new(Foo(6));
+    assert_eq!(generic.downcast_ref::<Foo>().unwrap().0, 6);
+}
+
+#[test]
+fn dispatch() {
+    fn describe(base: &dyn Base) -> String {
+        dispatch_downcast!(base, {
+            foo: Foo => format!("foo {}", foo.0),
+            Bar => "bar".to_string(),
+            _ => "other".to_string(),
+        })
+    }
+    assert_eq!(describe(&Foo(7)), "foo 7");
+    assert_eq!(describe(&Bar), "bar");
+}
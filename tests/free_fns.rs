@@ -0,0 +1,77 @@
+#[macro_use]
+extern crate downcast_rs;
+use downcast_rs::Downcast;
+
+trait Base: Downcast {}
+impl_downcast!(Base);
+
+// Methods with the same names as the generated ones, as a user might add.
+trait Shadowing {
+    fn downcast_ref(&self) -> &'static str;
+    fn downcast_mut(&mut self) -> &'static str;
+}
+impl Shadowing for dyn Base {
+    fn downcast_ref(&self) -> &'static str { "user downcast_ref" }
+    fn downcast_mut(&mut self) -> &'static str { "user downcast_mut" }
+}
+
+#[derive(Debug)]
+struct Foo(u32);
+impl Base for Foo {}
+#[derive(Debug)]
+struct Bar;
+impl Base for Bar {}
+
+// Generated code calls the free functions by their full path.
+macro_rules! foo_value {
+    ($obj:expr) => { ::downcast_rs::try_ref::<dyn Base, Foo>($obj).map(|foo| foo.0) };
+}
+
+#[test]
+fn free_fns_despite_shadowing_methods() {
+    let mut base: Box<dyn Base> = Box::new(Foo(1));
+    assert_eq!(Shadowing::downcast_ref(&*base), "user downcast_ref");
+    assert_eq!(Shadowing::downcast_mut(&mut *base), "user downcast_mut");
+
+    assert_eq!(foo_value!(&*base), Some(1));
+    assert!(downcast_rs::try_ref::<dyn Base, Bar>(&*base).is_none());
+    downcast_rs::try_mut::<dyn Base, Foo>(&mut *base).unwrap().0 = 2;
+    assert!(downcast_rs::try_mut::<dyn Base, Bar>(&mut *base).is_none());
+    assert_eq!(foo_value!(&*base), Some(2));
+
+    let base = downcast_rs::try_box::<dyn Base, Bar>(base).map(|_| ()).unwrap_err();
+    let foo = downcast_rs::try_box::<dyn Base, Foo>(base).map_err(|_| "Shouldn't happen.").unwrap();
+    assert_eq!(foo.0, 2);
+}
+
+// A trait that `impl_downcast!` was not invoked on.
+trait Plain: Downcast {}
+impl Plain for Foo {}
+
+fn generic_value<B: Downcast + ?Sized>(obj: &mut B) -> Option<u32> {
+    if !downcast_rs::is::<B, Foo>(obj) {
+        return None;
+    }
+    downcast_rs::downcast_mut::<B, Foo>(obj).unwrap().0 += 1;
+    downcast_rs::downcast_ref::<B, Foo>(obj).map(|obj| obj.0)
+}
+
+#[test]
+fn free_fns_on_trait_objects() {
+    let base: &dyn Base = &Foo(1);
+    assert!(downcast_rs::is::<_, Foo>(base));
+    assert!(!downcast_rs::is::<_, Bar>(base));
+    assert_eq!(downcast_rs::downcast_ref::<_, Foo>(base).map(|foo| foo.0), Some(1));
+    assert!(downcast_rs::downcast_ref::<_, Bar>(base).is_none());
+
+    let mut foo = Foo(1);
+    let base: &mut dyn Base = &mut foo;
+    assert_eq!(generic_value(base), Some(2));
+    assert!(downcast_rs::downcast_mut::<_, Bar>(base).is_none());
+    let mut bar = Bar;
+    assert_eq!(generic_value::<dyn Base>(&mut bar), None);
+
+    let plain: &mut dyn Plain = &mut foo;
+    assert_eq!(generic_value(plain), Some(3));
+    assert!(downcast_rs::is::<_, Foo>(plain));
+}
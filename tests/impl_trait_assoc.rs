@@ -0,0 +1,82 @@
+#![feature(type_alias_impl_trait, impl_trait_in_assoc_type)]
+
+#[macro_use]
+extern crate downcast_rs;
+use downcast_rs::Downcast;
+
+use std::fmt::Debug;
+
+trait Base: Downcast {
+    type H: Debug;
+    fn h(&self) -> Self::H;
+}
+
+// Concrete mode: the opaque type is named through a type alias.
+mod concrete {
+    use super::*;
+
+    pub type Opaque = impl Debug;
+
+    #[define_opaque(Opaque)]
+    fn make(n: u32) -> Opaque { n }
+
+    pub struct Foo(pub u32);
+    impl Base for Foo {
+        type H = Opaque;
+        fn h(&self) -> Opaque { make(self.0) }
+    }
+
+    pub struct Bar;
+    impl Base for Bar {
+        type H = Opaque;
+        fn h(&self) -> Opaque { make(0) }
+    }
+}
+impl_downcast!(concrete Base assoc H = concrete::Opaque);
+
+// Generic mode: the opaque associated type is simply one more instantiation of `H`.
+trait GenericBase: Downcast {
+    type H: Debug;
+    fn h(&self) -> Self::H;
+}
+impl_downcast!(GenericBase assoc H where H: Debug);
+
+struct Baz;
+impl GenericBase for Baz {
+    type H = impl Debug;
+    fn h(&self) -> Self::H { 7u8 }
+}
+
+// Shares `Baz`'s opaque type, so that both fit in the same trait object type.
+struct Qux;
+impl GenericBase for Qux {
+    type H = <Baz as GenericBase>::H;
+    fn h(&self) -> Self::H { Baz.h() }
+}
+
+#[test]
+fn concrete_opaque_assoc() {
+    let base: Box<dyn Base<H = concrete::Opaque>> = Box::new(concrete::Foo(42));
+    assert!(base.is::<concrete::Foo>());
+    assert!(!base.is::<concrete::Bar>());
+    assert_eq!(format!("{:?}", base.h()), "42");
+    let foo = base.downcast_ref::<concrete::Foo>().unwrap();
+    assert_eq!(foo.0, 42);
+    // A failed downcast hands back the trait object with its opaque associated type intact.
+    let base = base.downcast::<concrete::Bar>().map(|_| ()).unwrap_err();
+    assert_eq!(format!("{:?}", base.h()), "42");
+    assert_eq!(base.downcast::<concrete::Foo>().map_err(|_| "Shouldn't happen.").unwrap().0, 42);
+}
+
+#[test]
+fn generic_opaque_assoc() {
+    let objs: Vec<Box<dyn GenericBase<H = <Baz as GenericBase>::H>>> = vec![Box::new(Baz), Box::new(Qux)];
+    assert!(objs[0].is::<Baz>() && !objs[0].is::<Qux>());
+    assert!(objs[1].is::<Qux>() && !objs[1].is::<Baz>());
+    for obj in &objs {
+        assert_eq!(format!("{:?}", obj.h()), "7");
+    }
+    let mut objs = objs.into_iter();
+    assert!(objs.next().unwrap().downcast::<Baz>().is_ok());
+    assert!(objs.next().unwrap().downcast::<Baz>().is_err());
+}
@@ -0,0 +1,53 @@
+#[macro_use]
+extern crate downcast_rs;
+use std::rc::Rc;
+use downcast_rs::Downcast;
+
+trait Base: Downcast {}
+impl_downcast!(Base);
+
+#[derive(Debug, PartialEq)]
+struct Foo(u32);
+impl Base for Foo {}
+struct Bar;
+impl Base for Bar {}
+
+#[test]
+fn into_any_rc_direct() {
+    let rc: Rc<dyn Base> = Rc::new(Foo(1));
+    let foo = Downcast::into_any_rc(rc).downcast::<Foo>().unwrap();
+    assert_eq!(*foo, Foo(1));
+
+    let rc: Rc<dyn Base> = Rc::new(Bar);
+    let other = rc.clone();
+    let any = Downcast::into_any_rc(rc).downcast::<Foo>().unwrap_err();
+    // The very same allocation comes back as an `Rc<dyn Any>`.
+    assert!(any.is::<Bar>());
+    assert_eq!(Rc::strong_count(&other), 2);
+}
+
+#[test]
+fn into_any_direct() {
+    let boxed: Box<dyn Base> = Box::new(Foo(2));
+    assert_eq!(*Downcast::into_any(boxed).downcast::<Foo>().unwrap(), Foo(2));
+}
+
+#[cfg(feature = "sync")]
+mod sync {
+    use std::sync::Arc;
+    use downcast_rs::DowncastSync;
+
+    trait Base: DowncastSync {}
+    impl_downcast!(sync Base);
+
+    #[derive(Debug, PartialEq)]
+    struct Foo(u32);
+    impl Base for Foo {}
+
+    #[test]
+    fn into_any_arc_direct() {
+        let arc: Arc<dyn Base> = Arc::new(Foo(3));
+        let foo = DowncastSync::into_any_arc(arc).downcast::<Foo>().unwrap();
+        assert_eq!(*foo, Foo(3));
+    }
+}
@@ -0,0 +1,49 @@
+#[macro_use]
+extern crate downcast_rs;
+
+use downcast_rs::{registered_types, registration_of, Downcast};
+
+pub trait Plugin: Downcast {}
+impl_downcast!(Plugin);
+
+pub trait Other: Downcast {}
+impl_downcast!(Other);
+
+mod audio {
+    pub struct Mixer;
+    impl ::Plugin for Mixer {}
+    register_downcast!(::Plugin, Mixer);
+}
+
+mod video {
+    pub struct Decoder;
+    impl ::Plugin for Decoder {}
+    impl ::Other for Decoder {}
+    register_downcast!(::Plugin, Decoder);
+    register_downcast!(::Other, Decoder);
+}
+
+struct Unregistered;
+impl Plugin for Unregistered {}
+
+#[test]
+fn registered_from_modules() {
+    let mut names: Vec<_> = registered_types::<dyn Plugin>().map(|r| r.type_name()).collect();
+    names.sort();
+    assert_eq!(names, ["inventory::audio::Mixer", "inventory::video::Decoder"]);
+
+    let others: Vec<_> = registered_types::<dyn Other>().map(|r| r.type_name()).collect();
+    assert_eq!(others, ["inventory::video::Decoder"]);
+}
+
+#[test]
+fn registration_of_object() {
+    let plugin: Box<dyn Plugin> = Box::new(video::Decoder);
+    let registration = registration_of(&*plugin).unwrap();
+    assert_eq!(registration.type_id(), std::any::TypeId::of::<video::Decoder>());
+    assert!(registration.matches(&*plugin));
+    assert!(plugin.is::<video::Decoder>());
+
+    let plugin: Box<dyn Plugin> = Box::new(Unregistered);
+    assert!(registration_of(&*plugin).is_none());
+}
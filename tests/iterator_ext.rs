@@ -0,0 +1,41 @@
+#[macro_use]
+extern crate downcast_rs;
+use downcast_rs::{Downcast, DowncastIteratorExt};
+
+trait Base: Downcast {}
+impl_downcast!(Base);
+
+#[derive(Debug, PartialEq)]
+struct Foo(u32);
+impl Base for Foo {}
+struct Bar;
+impl Base for Bar {}
+
+fn mixed() -> Vec<Box<dyn Base>> {
+    vec![Box::new(Foo(1)), Box::new(Bar), Box::new(Foo(2)), Box::new(Bar), Box::new(Foo(3))]
+}
+
+#[test]
+fn filter_downcast_ref() {
+    let items = mixed();
+    let foos: Vec<&Foo> = items.iter().map(|obj| &**obj).filter_downcast_ref::<Foo>().collect();
+    assert_eq!(foos, [&Foo(1), &Foo(2), &Foo(3)]);
+    assert_eq!(items.iter().map(|obj| &**obj).filter_downcast_ref::<Bar>().count(), 2);
+}
+
+#[test]
+fn filter_downcast_mut() {
+    let mut items = mixed();
+    for foo in items.iter_mut().map(|obj| &mut **obj).filter_downcast_mut::<Foo>() {
+        foo.0 *= 10;
+    }
+    let foos: Vec<u32> = items.iter().filter_map(|obj| obj.downcast_ref::<Foo>()).map(|obj| obj.0).collect();
+    assert_eq!(foos, [10, 20, 30]);
+}
+
+#[test]
+fn filter_downcast_box() {
+    let foos: Vec<Box<Foo>> = mixed().into_iter().filter_downcast_box::<Foo>().collect();
+    assert_eq!(foos, [Box::new(Foo(1)), Box::new(Foo(2)), Box::new(Foo(3))]);
+    assert_eq!(mixed().into_iter().filter_downcast_box::<Bar>().size_hint(), (0, Some(5)));
+}
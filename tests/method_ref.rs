@@ -0,0 +1,35 @@
+#[macro_use]
+extern crate downcast_rs;
+use downcast_rs::Downcast;
+
+trait Node: Downcast {
+    fn parent(&self) -> Option<&dyn Node>;
+}
+impl_downcast!(Node extras => [downcast_ref_of]);
+
+struct Root(u32);
+impl Node for Root {
+    fn parent(&self) -> Option<&dyn Node> { None }
+}
+struct Leaf(Box<dyn Node>);
+impl Node for Leaf {
+    fn parent(&self) -> Option<&dyn Node> { Some(&*self.0) }
+}
+
+// The returned reference borrows from `node`, not from a temporary reborrow within the function.
+fn parent_of<T: Node>(node: &dyn Node) -> Option<&T> {
+    node.parent().and_then(<dyn Node>::downcast_ref_of::<T>)
+}
+
+#[test]
+fn downcast_method_returned_ref() {
+    let leaf: Box<dyn Node> = Box::new(Leaf(Box::new(Root(42))));
+    // `downcast_ref` works directly on the returned reference.
+    assert_eq!(leaf.parent().and_then(|parent| parent.downcast_ref::<Root>()).map(|root| root.0), Some(42));
+    assert!(leaf.parent().unwrap().downcast_ref::<Leaf>().is_none());
+
+    let root = parent_of::<Root>(&*leaf).unwrap();
+    assert_eq!(root.0, 42);
+    assert!(parent_of::<Leaf>(&*leaf).is_none());
+    assert!(parent_of::<Root>(root).is_none());
+}
@@ -0,0 +1,61 @@
+#[macro_use]
+extern crate downcast_rs;
+
+use std::rc::Rc;
+use downcast_rs::{Downcast, SingleImpl};
+
+trait A: Downcast {}
+trait B<T>: Downcast {}
+trait C: Downcast { type H; }
+trait D<T: Copy>: Downcast { type H; }
+trait E: Downcast + SingleImpl<Foo> {}
+
+impl_downcast! {
+    A;
+    B<T> clone => [Foo];
+    C assoc H where H: Copy;
+    concrete D<u32> assoc H = f32;
+    single E => Foo;
+}
+
+#[derive(Clone)]
+struct Foo(u32);
+impl A for Foo {}
+impl<T> B<T> for Foo {}
+impl C for Foo { type H = (); }
+impl D<u32> for Foo { type H = f32; }
+impl E for Foo {}
+
+struct Bar;
+impl A for Bar {}
+impl<T> B<T> for Bar {}
+impl C for Bar { type H = (); }
+impl D<u32> for Bar { type H = f32; }
+
+#[test]
+fn multiple_traits() {
+    let a: Box<dyn A> = Box::new(Foo(1));
+    assert!(a.is::<Foo>() && !a.is::<Bar>());
+    let b: Box<dyn B<u8>> = Box::new(Foo(2));
+    assert_eq!(b.clone_box().and_then(|b| b.downcast_ref::<Foo>().map(|foo| foo.0)), Some(2));
+    let c: Rc<dyn C<H = ()>> = Rc::new(Bar);
+    assert!(c.downcast_rc::<Bar>().is_ok());
+    let d: Box<dyn D<u32, H = f32>> = Box::new(Foo(4));
+    assert_eq!(d.downcast_ref::<Foo>().map(|foo| foo.0), Some(4));
+    let e: Box<dyn E> = Box::new(Foo(5));
+    assert_eq!(e.into_inner().0, 5);
+}
+
+// A trailing semicolon is allowed too.
+trait F: Downcast {}
+trait G: Downcast {}
+impl_downcast!(F; G;);
+impl F for Bar {}
+impl G for Bar {}
+
+#[test]
+fn trailing_semicolon() {
+    let f: &dyn F = &Bar;
+    let g: &dyn G = &Bar;
+    assert!(f.is::<Bar>() && g.is::<Bar>());
+}
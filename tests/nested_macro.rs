@@ -0,0 +1,53 @@
+#[macro_use]
+extern crate downcast_rs;
+
+use downcast_rs::Downcast;
+
+// Frameworks may define the downcastable trait in their own macro and invoke `impl_downcast!` on
+// it in the same expansion.
+macro_rules! define_base {
+    () => {
+        trait Base: Downcast {}
+        impl_downcast!(Base);
+    };
+}
+
+macro_rules! define_trait {
+    ($name:ident) => {
+        trait $name: Downcast {}
+        impl_downcast!($name);
+    };
+    ($name:ident < $($param:ident),* > assoc $($atype:ident),*) => {
+        trait $name<$($param),*>: Downcast { $(type $atype;)* }
+        impl_downcast!($name<$($param),*> assoc $($atype),*);
+    };
+}
+
+define_base!();
+define_trait!(Named);
+define_trait!(Generic<T> assoc H);
+
+struct Foo(u32);
+impl Base for Foo {}
+impl Named for Foo {}
+impl Generic<u8> for Foo { type H = u16; }
+struct Bar;
+impl Base for Bar {}
+impl Named for Bar {}
+impl Generic<u8> for Bar { type H = u16; }
+
+#[test]
+fn defined_by_wrapper_macro() {
+    let base: Box<dyn Base> = Box::new(Foo(1));
+    assert_eq!(base.downcast_ref::<Foo>().unwrap().0, 1);
+    assert!(!base.is::<Bar>());
+}
+
+#[test]
+fn named_by_wrapper_macro() {
+    let named: Box<dyn Named> = Box::new(Foo(2));
+    assert_eq!(named.downcast::<Foo>().map_err(|_| "Shouldn't happen.").unwrap().0, 2);
+    let generic: Box<dyn Generic<u8, H = u16>> = Box::new(Bar);
+    assert!(generic.is::<Bar>());
+    assert!(generic.downcast_ref::<Foo>().is_none());
+}
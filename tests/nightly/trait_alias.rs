@@ -0,0 +1,32 @@
+#![feature(trait_alias)]
+
+#[macro_use]
+extern crate downcast_rs;
+use downcast_rs::Downcast;
+
+use std::fmt::Debug;
+
+// Downcasting is implemented on the underlying trait, while the alias serves as a bound.
+trait Core: Downcast + Debug {}
+impl_downcast!(Core);
+
+trait Base = Core + Send;
+
+#[derive(Debug)]
+struct Foo(u32);
+impl Core for Foo {}
+#[derive(Debug)]
+struct Bar;
+impl Core for Bar {}
+
+fn boxed<T: Base>(value: T) -> Box<dyn Core> {
+    Box::new(value)
+}
+
+#[test]
+fn downcast_through_alias_bound() {
+    let base = boxed(Foo(1));
+    assert!(base.is::<Foo>());
+    assert_eq!(base.downcast_ref::<Foo>().unwrap().0, 1);
+    assert!(boxed(Bar).downcast_ref::<Foo>().is_none());
+}
@@ -0,0 +1,30 @@
+// Like the `once-lock` feature, requires Rust 1.70 rather than the minimum supported version.
+#![allow(unknown_lints, clippy::incompatible_msrv)]
+
+#[macro_use]
+extern crate downcast_rs;
+
+use std::sync::OnceLock;
+use downcast_rs::{downcast_static, DowncastSync};
+
+trait Plugin: DowncastSync {}
+impl_downcast!(sync Plugin);
+
+struct Foo(u32);
+impl Plugin for Foo {}
+struct Bar;
+impl Plugin for Bar {}
+
+static REGISTRY: OnceLock<Box<dyn Plugin>> = OnceLock::new();
+static UNSET: OnceLock<Box<dyn Plugin>> = OnceLock::new();
+
+#[test]
+fn downcast_static_once_lock() {
+    assert!(downcast_static::<Foo, _>(&UNSET).is_none());
+    assert!(UNSET.get().is_none());
+
+    assert!(REGISTRY.set(Box::new(Foo(42))).is_ok());
+    let foo: &'static Foo = downcast_static(&REGISTRY).unwrap();
+    assert_eq!(foo.0, 42);
+    assert!(downcast_static::<Bar, _>(&REGISTRY).is_none());
+}
@@ -0,0 +1,70 @@
+#[macro_use]
+extern crate downcast_rs;
+
+use std::convert::TryFrom;
+use downcast_rs::Downcast;
+
+trait Base: Downcast {}
+impl_downcast!(Base owned_enum BaseEnum => [Foo, Bar] extras => [concrete_layout]);
+
+trait Generic<T>: Downcast {}
+impl_downcast!(Generic<T> where T: Copy owned_enum GenericEnum => [Foo]);
+
+#[derive(Debug, PartialEq)]
+struct Foo(u32);
+impl Base for Foo {}
+impl<T: Copy> Generic<T> for Foo {}
+#[derive(Debug, PartialEq)]
+struct Bar;
+impl Base for Bar {}
+struct Baz;
+impl Base for Baz {}
+impl<T: Copy> Generic<T> for Baz {}
+
+// Generic code written against `TryFrom`.
+fn convert<T: TryFrom<U>, U>(value: U) -> Result<T, T::Error> {
+    T::try_from(value)
+}
+
+fn describe(base: Box<dyn Base>) -> String {
+    match convert::<BaseEnum<dyn Base>, _>(base) {
+        Ok(BaseEnum::Foo(obj)) => format!("Foo({})", obj.0),
+        Ok(BaseEnum::Bar(_)) => "Bar".to_string(),
+        Ok(BaseEnum::Other(other)) => format!("other of size {}", (*other).concrete_layout().size()),
+        Err(never) => match never {},
+    }
+}
+
+#[test]
+fn try_from_each_type() {
+    assert_eq!(describe(Box::new(Foo(42))), "Foo(42)");
+    assert_eq!(describe(Box::new(Bar)), "Bar");
+    assert_eq!(describe(Box::new(Baz)), "other of size 0");
+}
+
+#[test]
+fn from_keeps_the_box() {
+    let base: Box<dyn Base> = Box::new(Foo(7));
+    let addr = &*base as *const dyn Base as *const u8;
+    match BaseEnum::from(base) {
+        BaseEnum::Foo(obj) => assert_eq!(&*obj as *const Foo as *const u8, addr),
+        _ => panic!("expected a Foo"),
+    }
+
+    let other: BaseEnum<dyn Base> = (Box::new(Baz) as Box<dyn Base>).into();
+    match other {
+        BaseEnum::Other(obj) => assert!(obj.is::<Baz>()),
+        _ => panic!("expected another type"),
+    }
+}
+
+#[test]
+fn try_from_generic() {
+    let base: Box<dyn Generic<u8>> = Box::new(Foo(1));
+    match convert::<GenericEnum<dyn Generic<u8>>, _>(base) {
+        Ok(GenericEnum::Foo(obj)) => assert_eq!(*obj, Foo(1)),
+        _ => panic!("expected a Foo"),
+    }
+    let base: Box<dyn Generic<u8>> = Box::new(Baz);
+    assert!(match GenericEnum::from(base) { GenericEnum::Other(obj) => obj.is::<Baz>(), _ => false });
+}
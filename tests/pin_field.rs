@@ -0,0 +1,94 @@
+#[macro_use]
+extern crate downcast_rs;
+
+use std::marker::PhantomPinned;
+use std::pin::Pin;
+use downcast_rs::Downcast;
+
+trait State: Downcast {
+    fn name(&self) -> &'static str;
+}
+impl_downcast!(State unchecked);
+
+struct Idle;
+impl State for Idle {
+    fn name(&self) -> &'static str { "idle" }
+}
+
+// Not `Unpin`, so it can only be viewed pinned.
+struct Running {
+    ticks: u32,
+    _pinned: PhantomPinned,
+}
+impl State for Running {
+    fn name(&self) -> &'static str { "running" }
+}
+
+impl Running {
+    fn tick(self: Pin<&mut Self>) {
+        // SAFETY: `ticks` is not structurally pinned.
+        unsafe { self.get_unchecked_mut().ticks += 1 }
+    }
+}
+
+struct Machine {
+    state: Pin<Box<dyn State>>,
+}
+impl_pin_field_downcast!(Machine, state: State => state_as);
+
+impl Machine {
+    // A manual pin projection, as `#[pin_project]` would generate for the field.
+    fn state_mut(self: Pin<&mut Self>) -> Pin<&mut dyn State> {
+        self.get_mut().state.as_mut()
+    }
+}
+
+#[test]
+fn pin_field_downcast() {
+    let mut machine = Machine { state: Box::pin(Idle) };
+    assert!(machine.state_as::<Idle>().is_some());
+    assert!(machine.state_as::<Running>().is_none());
+
+    machine.state = Box::pin(Running { ticks: 3, _pinned: PhantomPinned });
+    let addr = &*machine.state as *const dyn State as *const Running;
+    let running: Pin<&Running> = machine.state_as::<Running>().unwrap();
+    assert_eq!(running.ticks, 3);
+    assert_eq!(&*running as *const Running, addr);
+    assert_eq!(machine.state.name(), "running");
+    assert!(machine.state_as::<Idle>().is_none());
+}
+
+#[test]
+fn pin_projection_downcast_mut() {
+    let mut machine = Machine { state: Box::pin(Running { ticks: 0, _pinned: PhantomPinned }) };
+    let mut machine = Pin::new(&mut machine);
+    assert!(machine.as_mut().state_mut().downcast_pin_mut::<Idle>().is_none());
+    let running: Pin<&mut Running> = machine.as_mut().state_mut().downcast_pin_mut().unwrap();
+    running.tick();
+    let running = machine.as_mut().state_mut().downcast_pin_mut::<Running>().unwrap();
+    assert_eq!(running.ticks, 1);
+    assert_eq!(machine.state.name(), "running");
+}
+
+#[test]
+fn downcast_pin_not_unpin() {
+    let pinned: Pin<Box<dyn State>> = Box::pin(Running { ticks: 5, _pinned: PhantomPinned });
+    let addr = &*pinned as *const dyn State as *const u8;
+
+    // A mismatch hands back the original pinned box, still in place.
+    let pinned = pinned.downcast_pin::<Idle>().map(|_| ()).unwrap_err();
+    assert_eq!(&*pinned as *const dyn State as *const u8, addr);
+
+    let mut running: Pin<Box<Running>> = pinned.downcast_pin::<Running>().map_err(|_| "not running").unwrap();
+    assert_eq!(&*running as *const Running as *const u8, addr);
+    running.as_mut().tick();
+    assert_eq!(running.ticks, 6);
+}
+
+#[test]
+fn downcast_pin_from_box() {
+    let boxed: Box<dyn State> = Box::new(Idle);
+    let pinned = Pin::from(boxed);
+    let pinned = pinned.downcast_pin::<Running>().map(|_| ()).unwrap_err();
+    assert!(pinned.downcast_pin::<Idle>().is_ok());
+}
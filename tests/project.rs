@@ -0,0 +1,52 @@
+#[macro_use]
+extern crate downcast_rs;
+use downcast_rs::Downcast;
+
+trait Base: Downcast {}
+impl_downcast!(Base extras => [project]);
+
+struct Foo { name: String }
+impl Base for Foo {}
+struct Bar { name: &'static str, id: u32 }
+impl Base for Bar {}
+struct Baz;
+impl Base for Baz {}
+
+// Without `extras => [project]`, a trait method of the same name is called as usual.
+trait Projector: Downcast { fn project(&self) -> u32; }
+impl_downcast!(Projector);
+impl Projector for Baz { fn project(&self) -> u32 { 3 } }
+
+fn name(base: &dyn Base) -> Option<&str> {
+    base.downcast_ref::<Foo>().map(|obj| obj.name.as_str())
+        .or_else(|| base.downcast_ref::<Bar>().map(|obj| obj.name))
+}
+
+#[test]
+fn project_shared_field() {
+    let objs: Vec<Box<dyn Base>> = vec![
+        Box::new(Foo { name: "foo".to_string() }),
+        Box::new(Bar { name: "bar", id: 1 }),
+        Box::new(Baz),
+    ];
+    let names: Vec<Option<&str>> = objs.iter().map(|obj| obj.project(name)).collect();
+    assert_eq!(names, [Some("foo"), Some("bar"), None]);
+}
+
+#[test]
+fn project_with_closure() {
+    let base: Box<dyn Base> = Box::new(Bar { name: "bar", id: 2 });
+    let id = base.project(|base| {
+        base.downcast_ref::<Foo>().map(|_| &0).or_else(|| base.downcast_ref::<Bar>().map(|obj| &obj.id))
+    });
+    assert_eq!(id, Some(&2));
+    let baz: Box<dyn Base> = Box::new(Baz);
+    assert_eq!(baz.project(|base| base.downcast_ref::<Bar>().map(|obj| &obj.id)), None);
+}
+
+#[test]
+fn trait_method_named_project() {
+    let baz: Box<dyn Projector> = Box::new(Baz);
+    assert_eq!(baz.project(), 3);
+    assert!(baz.is::<Baz>());
+}
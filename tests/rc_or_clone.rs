@@ -0,0 +1,59 @@
+#[macro_use]
+extern crate downcast_rs;
+
+use std::rc::Rc;
+use downcast_rs::Downcast;
+
+trait Base: Downcast {}
+impl_downcast!(Base extras => [downcast_rc_or_clone]);
+
+#[derive(Clone)]
+struct Foo(String);
+impl Base for Foo {}
+#[derive(Clone)]
+struct Bar;
+impl Base for Bar {}
+
+#[test]
+fn downcast_rc_or_clone_unique() {
+    let base: Rc<dyn Base> = Rc::new(Foo("foo".to_string()));
+    let addr = &*base as *const dyn Base as *const u8;
+    let foo = base.downcast_rc_or_clone::<Foo>().map_err(|_| "Shouldn't happen.").unwrap();
+    assert_eq!(foo.0, "foo");
+    // No clone is made of a uniquely owned object.
+    assert_eq!(&*foo as *const Foo as *const u8, addr);
+    assert_eq!(Rc::strong_count(&foo), 1);
+}
+
+#[test]
+fn downcast_rc_or_clone_shared() {
+    let base: Rc<dyn Base> = Rc::new(Foo("foo".to_string()));
+    let shared = base.clone();
+    let foo = base.downcast_rc_or_clone::<Foo>().map_err(|_| "Shouldn't happen.").unwrap();
+    assert_eq!(foo.0, "foo");
+    assert_eq!(Rc::strong_count(&foo), 1);
+    assert_ne!(&*foo as *const Foo as *const u8, &*shared as *const dyn Base as *const u8);
+    // The other owner still holds the original object.
+    assert_eq!(Rc::strong_count(&shared), 1);
+    assert_eq!(shared.downcast_ref::<Foo>().unwrap().0, "foo");
+}
+
+#[test]
+fn downcast_rc_or_clone_weak() {
+    let base: Rc<dyn Base> = Rc::new(Foo("foo".to_string()));
+    let weak = Rc::downgrade(&base);
+    let foo = base.downcast_rc_or_clone::<Foo>().map_err(|_| "Shouldn't happen.").unwrap();
+    assert_eq!(foo.0, "foo");
+    // The `Weak` keeps pointing at the original object, which is now dropped.
+    assert!(weak.upgrade().is_none());
+}
+
+#[test]
+fn downcast_rc_or_clone_mismatch() {
+    let base: Rc<dyn Base> = Rc::new(Bar);
+    let shared = base.clone();
+    let base = base.downcast_rc_or_clone::<Foo>().map(|_| ()).unwrap_err();
+    // The original `Rc` is handed back without cloning the object.
+    assert!(Rc::ptr_eq(&base, &shared));
+    assert!(base.is::<Bar>());
+}
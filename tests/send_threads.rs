@@ -0,0 +1,53 @@
+#[macro_use]
+extern crate downcast_rs;
+
+use std::any::Any;
+use std::cell::Cell;
+use std::thread;
+use downcast_rs::DowncastSend;
+
+trait Base: DowncastSend {}
+impl_downcast!(send Base);
+
+// `Send` but not `Sync`, so that `Base` could not extend `DowncastSync`.
+struct Foo(Cell<u32>);
+impl Base for Foo {}
+struct Bar;
+impl Base for Bar {}
+
+#[test]
+fn downcast_after_thread_round_trip() {
+    let base: Box<dyn Base> = Box::new(Foo(Cell::new(1)));
+    let base = thread::spawn(move || {
+        assert!(base.is::<Foo>());
+        base
+    }).join().unwrap();
+    let foo = base.downcast::<Foo>().map_err(|_| "not a Foo").unwrap();
+    assert_eq!(foo.0.get(), 1);
+}
+
+#[test]
+fn downcast_failure_stays_send() {
+    let base: Box<dyn Base> = Box::new(Bar);
+    let base = base.downcast::<Foo>().err().unwrap();
+    let base = thread::spawn(move || base).join().unwrap();
+    assert!(base.is::<Bar>());
+}
+
+#[test]
+fn into_any_send() {
+    let base: Box<dyn Base> = Box::new(Foo(Cell::new(2)));
+    let any: Box<dyn Any + Send> = base.into_any_send();
+    let any = thread::spawn(move || any).join().unwrap();
+    assert_eq!(any.downcast::<Foo>().unwrap().0.get(), 2);
+}
+
+trait Generic<T>: DowncastSend {}
+impl_downcast!(send concrete Generic<u32>);
+impl Generic<u32> for Bar {}
+
+#[test]
+fn send_concrete() {
+    let base: Box<dyn Generic<u32>> = Box::new(Bar);
+    assert!(base.into_any_send().is::<Bar>());
+}
@@ -0,0 +1,107 @@
+#![cfg(feature = "sync")]
+
+#[macro_use]
+extern crate downcast_rs;
+
+use std::any::Any;
+use std::sync::Arc;
+use std::thread;
+use downcast_rs::DowncastSync;
+
+trait Base: DowncastSync {}
+impl_downcast!(sync Base);
+
+trait Generic<T>: DowncastSync {}
+impl_downcast!(sync Generic<T> where T: Send + Sync);
+
+trait Assoc: DowncastSync { type H; }
+impl_downcast!(sync Assoc assoc H where H: Send + Sync);
+
+trait Concrete<T>: DowncastSync { type H; }
+impl_downcast!(concrete sync Concrete<u32> assoc H = u64);
+
+#[derive(Debug)]
+struct Foo(u32);
+impl Base for Foo {}
+impl<T> Generic<T> for Foo {}
+impl Assoc for Foo { type H = u8; }
+impl Concrete<u32> for Foo { type H = u64; }
+struct Bar;
+impl Base for Bar {}
+
+#[test]
+fn arc_round_trip_across_threads() {
+    let shared: Vec<Arc<dyn Base>> = vec![Arc::new(Foo(1)), Arc::new(Bar), Arc::new(Foo(2))];
+    let handles: Vec<_> = shared.iter().cloned().map(|obj| {
+        thread::spawn(move || obj.downcast_arc::<Foo>().ok().map(|foo| foo.0))
+    }).collect();
+    let results: Vec<Option<u32>> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+    assert_eq!(results, [Some(1), None, Some(2)]);
+}
+
+#[test]
+fn arc_failed_downcast_returns_original() {
+    let base: Arc<dyn Base> = Arc::new(Foo(1));
+    let other = base.clone();
+    let back = thread::spawn(move || other.downcast_arc::<Bar>().map(|_| ()).unwrap_err())
+        .join()
+        .unwrap();
+    // The very same allocation comes back; nothing was cloned.
+    assert!(Arc::ptr_eq(&back, &base));
+    assert_eq!(Arc::strong_count(&base), 2);
+}
+
+#[test]
+fn arc_all_forms() {
+    let generic: Arc<dyn Generic<String>> = Arc::new(Foo(3));
+    let generic = thread::spawn(move || generic).join().unwrap();
+    assert_eq!(generic.downcast_arc::<Foo>().map_err(|_| "not a Foo").unwrap().0, 3);
+
+    let assoc: Arc<dyn Assoc<H = u8>> = Arc::new(Foo(4));
+    let assoc = thread::spawn(move || assoc).join().unwrap();
+    assert_eq!(assoc.downcast_arc::<Foo>().map_err(|_| "not a Foo").unwrap().0, 4);
+
+    let concrete: Arc<dyn Concrete<u32, H = u64>> = Arc::new(Foo(5));
+    let concrete = thread::spawn(move || concrete).join().unwrap();
+    assert_eq!(concrete.downcast_arc::<Foo>().map_err(|_| "not a Foo").unwrap().0, 5);
+}
+
+// Holds type-erased references to be read from several threads.
+struct SharedRefs<'a>(Vec<&'a (dyn Any + Send + Sync)>);
+
+fn assert_send_sync<T: Send + Sync>(value: T) -> T { value }
+
+#[test]
+fn as_any_sync_keeps_markers() {
+    let objs: Arc<Vec<Box<dyn Base>>> = Arc::new(vec![Box::new(Foo(6)), Box::new(Bar)]);
+    let found = thread::spawn(move || {
+        // Dereferenced explicitly: `Box<dyn Base>` itself is `Any + Send + Sync` too.
+        let shared = assert_send_sync(SharedRefs(objs.iter().map(|obj| (**obj).as_any_sync()).collect()));
+        shared.0.iter().filter_map(|any| any.downcast_ref::<Foo>()).count()
+    }).join().unwrap();
+    assert_eq!(found, 1);
+
+    let mut foo: Box<dyn Base> = Box::new(Foo(7));
+    (*foo).as_any_sync_mut().downcast_mut::<Foo>().unwrap().0 += 1;
+    assert_eq!(foo.downcast_ref::<Foo>().unwrap().0, 8);
+}
+
+#[test]
+#[allow(dead_code)]
+fn downcast_arc_keeps_weak() {
+    trait Base: DowncastSync {}
+    impl_downcast!(sync Base);
+    struct Node { value: u32 }
+    impl Base for Node {}
+    struct Bar;
+    impl Base for Bar {}
+
+    let node = Arc::new(Node { value: 1 });
+    let weak = Arc::downgrade(&node);
+    let base: Arc<dyn Base> = node;
+    let base = base.downcast_arc::<Bar>().map(|_| ()).unwrap_err();
+    let node = base.downcast_arc::<Node>().map_err(|_| "Shouldn't happen.").unwrap();
+    assert_eq!(node.value, 1);
+    assert_eq!((Arc::strong_count(&node), Arc::weak_count(&node)), (1, 1));
+    assert!(Arc::ptr_eq(&weak.upgrade().unwrap(), &node));
+}
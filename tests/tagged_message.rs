@@ -0,0 +1,55 @@
+#[macro_use]
+extern crate downcast_rs;
+
+use std::any::TypeId;
+use std::sync::mpsc;
+use std::thread;
+use downcast_rs::{Downcast, TaggedMessage};
+
+trait Job: Downcast + Send {}
+impl_downcast!(Job extras => [tag]);
+
+struct Add(u32, u32);
+impl Job for Add {}
+struct Shout(String);
+impl Job for Shout {}
+struct Stop;
+impl Job for Stop {}
+
+#[test]
+fn tagged_messages_through_channel() {
+    let (tx, rx) = mpsc::channel::<TaggedMessage<dyn Job>>();
+    let worker = thread::spawn(move || {
+        let mut results = Vec::new();
+        for msg in rx {
+            let id = msg.type_id();
+            if id == TypeId::of::<Add>() {
+                let add = msg.downcast::<Add>().map_err(|_| "not an Add").unwrap();
+                results.push((add.0 + add.1).to_string());
+            } else if id == TypeId::of::<Shout>() {
+                let shout = msg.downcast::<Shout>().map_err(|_| "not a Shout").unwrap();
+                results.push(shout.0.to_uppercase());
+            } else {
+                assert!(msg.is::<Stop>());
+                break;
+            }
+        }
+        results
+    });
+
+    let jobs: Vec<Box<dyn Job>> = vec![Box::new(Add(1, 2)), Box::new(Shout("hi".to_string())), Box::new(Stop)];
+    for job in jobs {
+        tx.send(job.tag()).unwrap();
+    }
+    assert_eq!(worker.join().unwrap(), ["3", "HI"]);
+}
+
+#[test]
+fn tagged_message_mismatch() {
+    let job: Box<dyn Job> = Box::new(Add(1, 2));
+    let msg = job.tag();
+    assert_eq!(msg.type_id(), TypeId::of::<Add>());
+    assert!(!msg.is::<Shout>());
+    let msg = msg.downcast::<Shout>().map(|_| ()).unwrap_err();
+    assert!(msg.into_inner().is::<Add>());
+}
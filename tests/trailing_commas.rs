@@ -0,0 +1,64 @@
+#[macro_use]
+extern crate downcast_rs;
+use downcast_rs::Downcast;
+
+trait Params<T, U>: Downcast {}
+impl_downcast!(Params<T, U,>);
+
+trait Bounded<'a, T: Copy, const N: usize>: Downcast {}
+impl_downcast!(Bounded<'a, T: Copy, const N: usize,>);
+
+trait ConcreteParams<T, U>: Downcast {}
+impl_downcast!(concrete ConcreteParams<u32, f64,>);
+
+trait Assoc: Downcast { type G; type H; }
+impl_downcast!(Assoc assoc G, H,);
+
+trait ConcreteAssoc<T>: Downcast { type G; type H; }
+impl_downcast!(concrete ConcreteAssoc<u32,> assoc G = f64, H = u8,);
+
+trait AssocWhere<T>: Downcast { type H: Copy; }
+impl_downcast!(AssocWhere<T,> assoc H, where H: Copy);
+
+trait AssocOption: Downcast { type H; }
+impl_downcast!(AssocOption assoc H, kind => [Foo]);
+
+trait First: Downcast { type H; }
+trait Second: Downcast {}
+impl_downcast! {
+    concrete First assoc H = u8,;
+    Second;
+}
+
+struct Foo;
+impl<T, U> Params<T, U> for Foo {}
+impl<'a, T: Copy, const N: usize> Bounded<'a, T, N> for Foo {}
+impl ConcreteParams<u32, f64> for Foo {}
+impl Assoc for Foo { type G = u8; type H = u16; }
+impl ConcreteAssoc<u32> for Foo { type G = f64; type H = u8; }
+impl<T> AssocWhere<T> for Foo { type H = u8; }
+impl AssocOption for Foo { type H = u8; }
+impl First for Foo { type H = u8; }
+impl Second for Foo {}
+
+#[test]
+fn trailing_commas() {
+    let base: Box<dyn Params<u8, u16>> = Box::new(Foo);
+    assert!(base.is::<Foo>());
+    let base: Box<dyn Bounded<'static, u8, 4>> = Box::new(Foo);
+    assert!(base.is::<Foo>());
+    let base: Box<dyn ConcreteParams<u32, f64>> = Box::new(Foo);
+    assert!(base.is::<Foo>());
+    let base: Box<dyn Assoc<G = u8, H = u16>> = Box::new(Foo);
+    assert!(base.is::<Foo>());
+    let base: Box<dyn ConcreteAssoc<u32, G = f64, H = u8>> = Box::new(Foo);
+    assert!(base.is::<Foo>());
+    let base: Box<dyn AssocWhere<u8, H = u8>> = Box::new(Foo);
+    assert!(base.is::<Foo>());
+    let base: Box<dyn AssocOption<H = u8>> = Box::new(Foo);
+    assert!(base.kind() == TypeKind::Foo);
+    let base: Box<dyn First<H = u8>> = Box::new(Foo);
+    assert!(base.is::<Foo>());
+    let base: Box<dyn Second> = Box::new(Foo);
+    assert!(base.is::<Foo>());
+}
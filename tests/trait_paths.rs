@@ -0,0 +1,123 @@
+#[macro_use]
+extern crate downcast_rs;
+
+mod types {
+    use downcast_rs::Downcast;
+
+    pub trait Base: Downcast {}
+    #[cfg(feature = "sync")]
+    pub trait SyncBase: downcast_rs::DowncastSync {}
+    pub trait Generic<T: Copy>: Downcast {
+        type H;
+    }
+    pub trait Concrete<T: Copy>: Downcast {}
+
+    pub mod nested {
+        use downcast_rs::Downcast;
+
+        pub trait Inner: Downcast {}
+        impl_downcast!(self::Inner);
+        impl_downcast!(super::sibling::Sibling);
+
+        pub trait Outer: Downcast {}
+    }
+    impl_downcast!(self::nested::Outer);
+
+    pub mod sibling {
+        use downcast_rs::Downcast;
+
+        pub trait Sibling: Downcast {}
+    }
+
+    pub trait Single: Downcast + downcast_rs::SingleImpl<super::Foo> {}
+
+    pub mod deep {
+        pub mod deeper {
+            use downcast_rs::Downcast;
+
+            pub trait Deep<T>: Downcast {}
+            pub trait Relative: Downcast {}
+            pub trait Global: Downcast {}
+        }
+    }
+}
+
+impl_downcast!(crate::types::Base);
+#[cfg(feature = "sync")]
+impl_downcast!(sync crate::types::SyncBase);
+impl_downcast!(crate::types::Generic<T> assoc H where T: Copy);
+impl_downcast!(concrete self::types::Concrete<u32>);
+impl_downcast!(single crate::types::Single => Foo);
+impl_downcast!(crate::types::deep::deeper::Deep<T>);
+impl_downcast!(types::deep::deeper::Relative);
+// A global path, which edition 2015 resolves from the crate root.
+impl_downcast!(::types::deep::deeper::Global);
+
+#[derive(Debug)]
+struct Foo(u32);
+impl types::Base for Foo {}
+#[cfg(feature = "sync")]
+impl types::SyncBase for Foo {}
+impl types::Generic<u8> for Foo { type H = f32; }
+impl types::Concrete<u32> for Foo {}
+impl types::nested::Inner for Foo {}
+impl types::nested::Outer for Foo {}
+impl types::sibling::Sibling for Foo {}
+impl types::Single for Foo {}
+impl<T> types::deep::deeper::Deep<T> for Foo {}
+impl types::deep::deeper::Relative for Foo {}
+impl types::deep::deeper::Global for Foo {}
+#[derive(Debug)]
+struct Bar;
+impl types::Base for Bar {}
+
+#[test]
+fn crate_path() {
+    let base: Box<dyn types::Base> = Box::new(Foo(1));
+    assert!(!base.is::<Bar>());
+    assert_eq!(base.downcast_ref::<Foo>().unwrap().0, 1);
+
+    #[cfg(feature = "sync")]
+    {
+        let base: std::sync::Arc<dyn types::SyncBase> = std::sync::Arc::new(Foo(2));
+        assert_eq!(base.downcast_arc::<Foo>().map_err(|_| "Shouldn't happen.").unwrap().0, 2);
+    }
+
+    let base: Box<dyn types::Generic<u8, H = f32>> = Box::new(Foo(3));
+    assert_eq!(base.downcast_ref::<Foo>().unwrap().0, 3);
+
+    let base: Box<dyn types::Single> = Box::new(Foo(4));
+    assert_eq!(base.into_inner().0, 4);
+}
+
+#[test]
+fn self_path() {
+    let base: Box<dyn types::Concrete<u32>> = Box::new(Foo(1));
+    assert_eq!(base.downcast_ref::<Foo>().unwrap().0, 1);
+
+    let base: Box<dyn types::nested::Inner> = Box::new(Foo(2));
+    assert_eq!(base.downcast_ref::<Foo>().unwrap().0, 2);
+
+    let base: Box<dyn types::nested::Outer> = Box::new(Foo(3));
+    assert_eq!(base.downcast_ref::<Foo>().unwrap().0, 3);
+}
+
+#[test]
+fn super_path() {
+    let base: Box<dyn types::sibling::Sibling> = Box::new(Foo(1));
+    assert_eq!(base.downcast_ref::<Foo>().unwrap().0, 1);
+}
+
+#[test]
+fn nested_path() {
+    use types::deep::deeper::{Deep, Global, Relative};
+
+    let base: Box<dyn Deep<u8>> = Box::new(Foo(1));
+    assert_eq!(base.downcast_ref::<Foo>().unwrap().0, 1);
+
+    let base: Box<dyn Relative> = Box::new(Foo(2));
+    assert_eq!(base.downcast_ref::<Foo>().unwrap().0, 2);
+
+    let base: Box<dyn Global> = Box::new(Foo(3));
+    assert_eq!(base.downcast_ref::<Foo>().unwrap().0, 3);
+}
@@ -0,0 +1,46 @@
+#[macro_use]
+extern crate downcast_rs;
+
+use std::convert::{TryFrom, TryInto};
+use downcast_rs::Downcast;
+
+trait Base: Downcast {}
+impl_downcast!(Base try_from => [Foo, Bar]);
+
+trait Generic<T>: Downcast {}
+impl_downcast!(Generic<T> where T: Copy try_from => [Foo]);
+
+#[derive(Debug)]
+struct Foo(u32);
+impl Base for Foo {}
+impl<T: Copy> Generic<T> for Foo {}
+#[derive(Debug)]
+struct Bar;
+impl Base for Bar {}
+struct Baz;
+impl Base for Baz {}
+
+#[test]
+fn try_into_box() {
+    let base: Box<dyn Base> = Box::new(Foo(1));
+    let foo: Box<Foo> = base.try_into().map_err(|_| "Shouldn't happen.").unwrap();
+    assert_eq!(foo.0, 1);
+
+    let base: Box<dyn Base> = Box::new(Baz);
+    let base = <Box<Foo>>::try_from(base).unwrap_err();
+    let base = <Box<Bar>>::try_from(base).unwrap_err();
+    assert!(base.is::<Baz>());
+}
+
+// Generic code written against the standard conversion traits.
+fn convert<T, U: TryFrom<T>>(value: T) -> Option<U> {
+    U::try_from(value).ok()
+}
+
+#[test]
+fn try_from_in_generic_code() {
+    let base: Box<dyn Base> = Box::new(Bar);
+    assert!(convert::<_, Box<Foo>>(base).is_none());
+    let base: Box<dyn Generic<u8>> = Box::new(Foo(2));
+    assert_eq!(convert::<_, Box<Foo>>(base).map(|obj| obj.0), Some(2));
+}
@@ -0,0 +1,30 @@
+#[macro_use]
+extern crate downcast_rs;
+
+use downcast_rs::{register_type_code, Downcast};
+
+trait Message: Downcast {}
+impl_downcast!(Message extras => [concrete_type_code]);
+
+struct Ping;
+impl Message for Ping {}
+struct Pong;
+impl Message for Pong {}
+struct Unknown;
+impl Message for Unknown {}
+
+// A single test, since codes depend on the order of registration across the whole binary.
+#[test]
+fn registered_type_codes() {
+    assert_eq!(register_type_code::<Ping>(), 0);
+    assert_eq!(register_type_code::<Pong>(), 1);
+    // Registering again keeps the existing code.
+    assert_eq!(register_type_code::<Ping>(), 0);
+
+    let messages: Vec<Box<dyn Message>> = vec![Box::new(Pong), Box::new(Ping), Box::new(Unknown)];
+    let codes: Vec<Option<u32>> = messages.iter().map(|m| m.concrete_type_code()).collect();
+    assert_eq!(codes, [Some(1), Some(0), None]);
+
+    assert_eq!(register_type_code::<Unknown>(), 2);
+    assert_eq!(messages[2].concrete_type_code(), Some(2));
+}
@@ -0,0 +1,54 @@
+#[macro_use]
+extern crate downcast_rs;
+
+// Stands in for another crate, whose traits cannot be changed to extend `Downcast`.
+mod foreign {
+    pub trait Shape {
+        fn area(&self) -> f64;
+    }
+    pub trait Named<T> {
+        fn name(&self) -> T;
+    }
+}
+
+wrap_downcast!(foreign::Shape as LocalShape);
+wrap_downcast!(pub(crate) foreign::Named<&'static str> as LocalNamed);
+
+struct Square(f64);
+impl foreign::Shape for Square {
+    fn area(&self) -> f64 { self.0 * self.0 }
+}
+impl foreign::Named<&'static str> for Square {
+    fn name(&self) -> &'static str { "square" }
+}
+struct Circle(f64);
+impl foreign::Shape for Circle {
+    fn area(&self) -> f64 { 3.0 * self.0 * self.0 }
+}
+
+fn total_area(shapes: &[&dyn foreign::Shape]) -> f64 {
+    shapes.iter().map(|shape| shape.area()).sum()
+}
+
+#[test]
+fn downcast_wrapped_foreign_trait() {
+    let shapes: Vec<Box<dyn LocalShape>> = vec![Box::new(Square(2.0)), Box::new(Circle(1.0))];
+    assert_eq!(shapes[0].area(), 4.0);
+    assert_eq!(shapes[0].downcast_ref::<Square>().unwrap().0, 2.0);
+    assert!(shapes[0].downcast_ref::<Circle>().is_none());
+    assert!(shapes[1].is::<Circle>());
+
+    let wrapped: Vec<&dyn foreign::Shape> = shapes.iter().map(|shape| shape.as_wrapped()).collect();
+    assert_eq!(total_area(&wrapped), 7.0);
+
+    let mut shapes = shapes;
+    let circle = shapes.pop().unwrap().downcast::<Circle>().map_err(|_| "Shouldn't happen.").unwrap();
+    assert_eq!(circle.0, 1.0);
+}
+
+#[test]
+fn downcast_wrapped_generic_foreign_trait() {
+    let named: Box<dyn LocalNamed> = Box::new(Square(1.0));
+    assert_eq!(named.name(), "square");
+    assert!(named.is::<Square>());
+}